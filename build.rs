@@ -38,6 +38,11 @@ fn pango() -> Result<(), Error> {
 }
 
 fn main() {
+	// The headless core has no C dependencies.
+	if env::var("CARGO_FEATURE_IMAGES").is_err() {
+		return;
+	}
+
 	cairo().unwrap();
 	pango().unwrap();
 }
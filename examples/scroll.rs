@@ -0,0 +1,92 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Measure the memory used by the scroll back, it reports the size per 10k
+//! lines in compact and fully inflated form.
+
+extern crate cancer;
+extern crate env_logger;
+extern crate clap;
+
+use std::rc::Rc;
+use std::time::Instant;
+
+use clap::{App, Arg};
+use cancer::style::{self, Style};
+use cancer::terminal::Grid;
+use cancer::terminal::grid::History;
+
+fn main() {
+	env_logger::init();
+
+	let matches = App::new("cancer")
+		.version(env!("CARGO_PKG_VERSION"))
+		.author("meh. <meh@schizofreni.co>")
+		.arg(Arg::with_name("lines")
+			.short("l")
+			.long("lines")
+			.takes_value(true)
+			.help("Number of lines to scroll into the scroll back."))
+		.arg(Arg::with_name("columns")
+			.short("c")
+			.long("columns")
+			.takes_value(true)
+			.help("Number of columns of the grid."))
+		.get_matches();
+
+	let lines   = matches.value_of("lines").and_then(|v| v.parse().ok()).unwrap_or(100_000u32);
+	let columns = matches.value_of("columns").and_then(|v| v.parse().ok()).unwrap_or(120u32);
+	let rows    = 24;
+
+	let plain = Rc::new(Style::default());
+	let bold  = Rc::new(Style { attributes: style::BOLD, .. Style::default() });
+
+	let mut grid  = Grid::new(columns, rows, History::Unlimited, None);
+	let     start = Instant::now();
+
+	// Fill the last row with log-like lines, then scroll it into the scroll back.
+	for line in 0 .. lines {
+		let text = format!("{:>8} [INFO] request handled in {}ms by worker {}", line, line % 997, line % 13);
+
+		for x in 0 .. columns {
+			grid[(x, rows - 1)].make_empty(plain.clone());
+		}
+
+		for (x, ch) in text.chars().take(columns as usize).enumerate() {
+			grid[(x as u32, rows - 1)].make_occupied(ch.to_string(),
+				if x >= 9 && x < 15 { bold.clone() } else { plain.clone() });
+		}
+
+		grid.up(1, None);
+	}
+
+	let elapsed = start.elapsed();
+
+	// Measure what the same rows would take when fully inflated.
+	let mut full = 0;
+	for i in 0 .. grid.back().len() {
+		full += grid.back().row(i).map(|r| r.size()).unwrap_or(0);
+	}
+
+	let per = |size: usize| size as f64 / grid.back().len() as f64 * 10_000.0 / 1024.0;
+
+	println!("lines:   {}", grid.back().len());
+	println!("columns: {}", columns);
+	println!("time:    {}.{:03}s", elapsed.as_secs(), elapsed.subsec_nanos() / 1_000_000);
+	println!("compact: {:.1} KiB per 10k lines", per(grid.size()));
+	println!("full:    {:.1} KiB per 10k lines", per(full));
+}
@@ -0,0 +1,112 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Sessions in the [asciicast v2](https://docs.asciinema.org/manual/asciicast/v2/)
+//! format.
+
+mod recorder;
+pub use self::recorder::Recorder;
+
+mod player;
+pub use self::player::Player;
+
+use std::char;
+
+/// Encode a JSON string.
+pub fn encode(value: &str) -> String {
+	let mut output = String::with_capacity(value.len() + 2);
+	output.push('"');
+
+	for ch in value.chars() {
+		match ch {
+			'"'  => output.push_str("\\\""),
+			'\\' => output.push_str("\\\\"),
+			'\n' => output.push_str("\\n"),
+			'\r' => output.push_str("\\r"),
+			'\t' => output.push_str("\\t"),
+
+			c if (c as u32) < 0x20 || c as u32 == 0x7F =>
+				output.push_str(&format!("\\u{:04x}", c as u32)),
+
+			c =>
+				output.push(c),
+		}
+	}
+
+	output.push('"');
+	output
+}
+
+/// Decode a JSON string at the start of the input, returning the rest.
+pub fn decode(input: &str) -> Option<(String, &str)> {
+	fn hex(input: &str) -> Option<u32> {
+		if input.len() < 4 || !input.is_char_boundary(4) {
+			return None;
+		}
+
+		u32::from_str_radix(&input[.. 4], 16).ok()
+	}
+
+	let     input  = input.trim_left();
+	let mut output = String::new();
+
+	if !input.starts_with('"') {
+		return None;
+	}
+
+	let mut rest = &input[1 ..];
+	loop {
+		let end = try!(option rest.find(|c| c == '"' || c == '\\'));
+		output.push_str(&rest[.. end]);
+
+		if rest.as_bytes()[end] == b'"' {
+			return Some((output, &rest[end + 1 ..]));
+		}
+
+		rest = &rest[end + 1 ..];
+		let escape = try!(option rest.chars().next());
+		rest = &rest[escape.len_utf8() ..];
+
+		match escape {
+			'n' => output.push('\n'),
+			'r' => output.push('\r'),
+			't' => output.push('\t'),
+			'b' => output.push('\x08'),
+			'f' => output.push('\x0C'),
+
+			'u' => {
+				let mut code = try!(option hex(rest));
+				rest = &rest[4 ..];
+
+				// Characters outside the BMP are split in surrogate pairs.
+				if code >= 0xD800 && code < 0xDC00 && rest.starts_with("\\u") {
+					if let Some(low) = hex(&rest[2 ..]) {
+						if low >= 0xDC00 && low < 0xE000 {
+							code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+							rest = &rest[6 ..];
+						}
+					}
+				}
+
+				output.push(char::from_u32(code).unwrap_or('\u{FFFD}'));
+			}
+
+			c =>
+				output.push(c),
+		}
+	}
+}
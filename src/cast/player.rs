@@ -0,0 +1,261 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+use std::sync::mpsc::{Sender, Receiver, RecvTimeoutError, channel};
+
+use error::{self, Error};
+use platform::Source;
+use interface::Replay;
+use cast::decode;
+
+/// Plays a recording back in place of a tty.
+#[derive(Debug)]
+pub struct Player {
+	output:   Option<Receiver<Vec<u8>>>,
+	resizes:  Option<Receiver<(u32, u32)>>,
+	controls: Sender<Replay>,
+}
+
+#[derive(Debug)]
+enum Frame {
+	Output(Vec<u8>),
+	Resize(u32, u32),
+}
+
+/// The playback state changed by the controls.
+#[derive(Debug)]
+struct State {
+	speed:  f64,
+	paused: bool,
+	steps:  u32,
+}
+
+impl State {
+	fn handle(&mut self, control: Replay) {
+		debug!(target: "cancer::cast", "replay control: {:?}", control);
+
+		match control {
+			Replay::Pause => {
+				self.paused = !self.paused;
+				self.steps  = 0;
+			}
+
+			Replay::Step(n) if self.paused => {
+				self.steps += n;
+			}
+
+			Replay::Step(..) =>
+				(),
+
+			Replay::Faster => {
+				self.speed = (self.speed * 2.0).min(64.0);
+			}
+
+			Replay::Slower => {
+				self.speed = (self.speed / 2.0).max(1.0 / 64.0);
+			}
+		}
+	}
+}
+
+impl Player {
+	/// Load the recording and start playing it at the given speed.
+	pub fn open<P: AsRef<Path>>(path: P, speed: f64) -> error::Result<Self> {
+		let mut content = String::new();
+		File::open(path)?.read_to_string(&mut content)?;
+
+		let mut lines  = content.lines();
+		let     header = lines.next().unwrap_or("");
+		let     size   = match (field(header, "width"), field(header, "height")) {
+			(Some(width), Some(height)) =>
+				(width, height),
+
+			_ =>
+				return Err(Error::Message("invalid asciicast header".into()))
+		};
+
+		let frames = lines.filter_map(event).collect::<Vec<_>>();
+		debug!(target: "cancer::cast", "replaying {} events at {}x{}", frames.len(), size.0, size.1);
+
+		let (o_sender, o_receiver) = channel();
+		let (r_sender, r_receiver) = channel();
+		let (c_sender, c_receiver) = channel();
+
+		thread::Builder::new().name("cancer::replay".into()).spawn(move || {
+			let mut state = State { speed: if speed > 0.0 { speed } else { 1.0 }, paused: false, steps: 0 };
+			let mut last  = 0.0;
+
+			// Start from the recorded size.
+			let _ = r_sender.send(size);
+
+			for (time, frame) in frames {
+				let mut delay = seconds((time - last).max(0.0) / state.speed);
+				last = time;
+
+				loop {
+					// While paused only steps let frames through.
+					if state.paused {
+						if state.steps > 0 {
+							state.steps -= 1;
+							break;
+						}
+
+						match c_receiver.recv() {
+							Ok(control) => state.handle(control),
+							Err(_)      => return,
+						}
+
+						continue;
+					}
+
+					let started = Instant::now();
+					match c_receiver.recv_timeout(delay) {
+						Ok(control) => {
+							state.handle(control);
+							delay = delay.checked_sub(started.elapsed()).unwrap_or(Duration::from_millis(0));
+						}
+
+						Err(RecvTimeoutError::Timeout) =>
+							break,
+
+						Err(RecvTimeoutError::Disconnected) =>
+							return,
+					}
+				}
+
+				let sent = match frame {
+					Frame::Output(data) =>
+						o_sender.send(data).is_ok(),
+
+					Frame::Resize(columns, rows) =>
+						r_sender.send((columns, rows)).is_ok(),
+				};
+
+				if !sent {
+					return;
+				}
+			}
+
+			// Keep the terminal around once the recording is over.
+			debug!(target: "cancer::cast", "replay finished");
+			while c_receiver.recv().is_ok() { }
+		}).unwrap();
+
+		Ok(Player {
+			output:   Some(o_receiver),
+			resizes:  Some(r_receiver),
+			controls: c_sender,
+		})
+	}
+
+	/// Take the receiving end of the recorded resizes.
+	pub fn resizes(&mut self) -> Receiver<(u32, u32)> {
+		self.resizes.take().unwrap()
+	}
+
+	/// Get a handle to control the playback.
+	pub fn controls(&self) -> Sender<Replay> {
+		self.controls.clone()
+	}
+}
+
+impl Source for Player {
+	fn output(&mut self) -> Receiver<Vec<u8>> {
+		self.output.take().unwrap()
+	}
+
+	fn resize(&mut self, _width: u32, _height: u32) -> error::Result<()> {
+		Ok(())
+	}
+}
+
+/// Replies from the terminal have nowhere to go.
+impl Write for Player {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+/// Find an integer field in the header.
+fn field(header: &str, name: &str) -> Option<u32> {
+	let start = try!(option header.find(&format!("\"{}\"", name))) + name.len() + 2;
+	let rest  = header[start ..].trim_left();
+
+	if !rest.starts_with(':') {
+		return None;
+	}
+
+	let rest = rest[1 ..].trim_left();
+	let end  = rest.find(|c: char| !c.is_digit(10)).unwrap_or(rest.len());
+
+	rest[.. end].parse().ok()
+}
+
+/// Parse an event line, input and markers are skipped.
+fn event(line: &str) -> Option<(f64, Frame)> {
+	let line = line.trim();
+
+	if !line.starts_with('[') {
+		return None;
+	}
+
+	let comma = try!(option line.find(','));
+	let time  = try!(option line[1 .. comma].trim().parse::<f64>().ok());
+
+	let (code, rest) = try!(option decode(&line[comma + 1 ..]));
+	let rest         = rest.trim_left();
+
+	if !rest.starts_with(',') {
+		return None;
+	}
+
+	let (data, _) = try!(option decode(&rest[1 ..]));
+
+	match &*code {
+		"o" =>
+			Some((time, Frame::Output(data.into_bytes()))),
+
+		"r" => {
+			let mut size = data.split('x').map(|v| v.trim().parse::<u32>());
+
+			match (size.next(), size.next()) {
+				(Some(Ok(columns)), Some(Ok(rows))) =>
+					Some((time, Frame::Resize(columns, rows))),
+
+				_ =>
+					None
+			}
+		}
+
+		_ =>
+			None
+	}
+}
+
+/// Turn fractional seconds into a duration.
+fn seconds(value: f64) -> Duration {
+	Duration::new(value.trunc() as u64, (value.fract() * 1_000_000_000.0) as u32)
+}
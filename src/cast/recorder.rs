@@ -0,0 +1,119 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write, BufWriter};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use std::str;
+
+use error;
+use cast::encode;
+
+/// Records the tty output with timestamps.
+#[derive(Debug)]
+pub struct Recorder {
+	file:    BufWriter<File>,
+	start:   Instant,
+	pending: Vec<u8>,
+	failed:  bool,
+}
+
+impl Recorder {
+	/// Create the recording and write the header.
+	pub fn create<P: AsRef<Path>>(path: P, (columns, rows): (u32, u32), term: Option<&str>) -> error::Result<Self> {
+		// The recording has everything that went through the terminal.
+		let mut file      = BufWriter::new(OpenOptions::new().write(true).create(true).truncate(true).mode(0o600).open(path)?);
+		let     timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+
+		writeln!(file, r#"{{"version": 2, "width": {}, "height": {}, "timestamp": {}, "env": {{"TERM": {}}}}}"#,
+			columns, rows, timestamp, encode(term.unwrap_or("cancer")))?;
+		file.flush()?;
+
+		Ok(Recorder {
+			file:    file,
+			start:   Instant::now(),
+			pending: Vec::new(),
+			failed:  false,
+		})
+	}
+
+	/// Record a chunk of output.
+	pub fn output(&mut self, data: &[u8]) {
+		self.pending.extend_from_slice(data);
+
+		// Output is recorded as text, a sequence split between two chunks is
+		// kept for the next one and invalid bytes are replaced.
+		let mut text = String::new();
+		let mut rest = 0;
+
+		loop {
+			match str::from_utf8(&self.pending[rest ..]) {
+				Ok(valid) => {
+					text.push_str(valid);
+					rest = self.pending.len();
+					break;
+				}
+
+				Err(err) => {
+					let valid = err.valid_up_to();
+					text.push_str(unsafe { str::from_utf8_unchecked(&self.pending[rest .. rest + valid]) });
+					rest += valid;
+
+					if let Some(length) = err.error_len() {
+						text.push('\u{FFFD}');
+						rest += length;
+					}
+					else {
+						break;
+					}
+				}
+			}
+		}
+
+		self.pending.drain(.. rest);
+
+		if !text.is_empty() {
+			self.event("o", &text);
+		}
+	}
+
+	/// Record a resize.
+	pub fn resize(&mut self, columns: u32, rows: u32) {
+		self.event("r", &format!("{}x{}", columns, rows));
+	}
+
+	fn event(&mut self, code: &str, data: &str) {
+		if self.failed {
+			return;
+		}
+
+		let elapsed = self.start.elapsed();
+		let time    = elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 / 1_000_000_000.0;
+
+		if let Err(err) = self.write(time, code, data) {
+			error!(target: "cancer::cast", "recording failed: {:?}", err);
+			self.failed = true;
+		}
+	}
+
+	fn write(&mut self, time: f64, code: &str, data: &str) -> io::Result<()> {
+		writeln!(self.file, "[{:.6}, {}, {}]", time, encode(code), encode(data))?;
+		self.file.flush()
+	}
+}
@@ -16,14 +16,25 @@
 // along with cancer.  If not, see <http://www.gnu.org/licenses/>.
 
 use toml::{self, Value};
+use terminal::encoding::Encoding;
+use terminal::grid::History;
+
+/// The default size limit of the rows spilled to disk.
+const SPILL: usize = 1024 * 1024 * 1024;
 
 #[derive(PartialEq, Clone, Debug)]
 pub struct Environment {
-	program: Option<String>,
-	term:    Option<String>,
+	program:  Option<String>,
+	term:     Option<String>,
+	encoding: Encoding,
+	answer:   String,
+	printer:  Option<Printer>,
+	log:      Log,
+	socket:   Option<String>,
 
 	cache:  usize,
-	scroll: usize,
+	scroll: History,
+	spill:  Option<usize>,
 	batch:  Option<u32>,
 
 	x11:   X11,
@@ -33,11 +44,17 @@ pub struct Environment {
 impl Default for Environment {
 	fn default() -> Self {
 		Environment {
-			program: None,
-			term:    None,
+			program:  None,
+			term:     None,
+			encoding: Encoding::default(),
+			answer:   String::new(),
+			printer:  None,
+			log:      Log::default(),
+			socket:   None,
 
 			cache:  4096,
-			scroll: 4096,
+			scroll: History::Rows(4096),
+			spill:  None,
 			batch:  Some(16),
 
 			x11:   Default::default(),
@@ -46,6 +63,47 @@ impl Default for Environment {
 	}
 }
 
+/// Where media copies are sent.
+#[derive(PartialEq, Clone, Debug)]
+pub enum Printer {
+	/// A shell command receiving the data on its standard input.
+	Command(String),
+
+	/// A file the data is appended to.
+	File(String),
+}
+
+/// Where and how the session is logged.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Log {
+	path:    String,
+	mode:    Logging,
+	enabled: bool,
+}
+
+/// What ends up in the session log.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Logging {
+	/// The tty byte stream as is.
+	Raw,
+
+	/// Completed lines without escape sequences.
+	Plain,
+
+	/// Completed lines prefixed with the time.
+	Timestamped,
+}
+
+impl Default for Log {
+	fn default() -> Self {
+		Log {
+			path:    "~/cancer.log".into(),
+			mode:    Logging::Plain,
+			enabled: false,
+		}
+	}
+}
+
 #[derive(PartialEq, Clone, Debug)]
 pub struct X11 {
 	display: Option<String>,
@@ -84,6 +142,82 @@ impl Environment {
 			self.term = Some(value.into());
 		}
 
+		if let Some(value) = table.get("encoding").and_then(|v| v.as_str()) {
+			if let Some(value) = Encoding::parse(value) {
+				self.encoding = value;
+			}
+			else {
+				error!(target: "cancer::config", "[environment.encoding] unknown encoding: {}", value);
+			}
+		}
+
+		if let Some(value) = table.get("answerback").and_then(|v| v.as_str()) {
+			self.answer = value.into();
+		}
+
+		if let Some(value) = table.get("printer") {
+			match *value {
+				Value::String(ref value) =>
+					self.printer = Some(Printer::Command(value.clone())),
+
+				Value::Table(ref table) => {
+					if let Some(value) = table.get("file").and_then(|v| v.as_str()) {
+						self.printer = Some(Printer::File(value.into()));
+					}
+					else if let Some(value) = table.get("command").and_then(|v| v.as_str()) {
+						self.printer = Some(Printer::Command(value.into()));
+					}
+				}
+
+				_ => ()
+			}
+		}
+
+		if let Some(value) = table.get("log") {
+			match *value {
+				Value::String(ref value) => {
+					self.log.path    = value.clone();
+					self.log.enabled = true;
+				}
+
+				Value::Table(ref table) => {
+					if let Some(value) = table.get("path").and_then(|v| v.as_str()) {
+						self.log.path = value.into();
+					}
+
+					if let Some(value) = table.get("mode").and_then(|v| v.as_str()) {
+						match value {
+							"raw"         => self.log.mode = Logging::Raw,
+							"plain"       => self.log.mode = Logging::Plain,
+							"timestamped" => self.log.mode = Logging::Timestamped,
+
+							_ =>
+								error!(target: "cancer::config", "[environment.log] unknown mode: {}", value),
+						}
+					}
+
+					self.log.enabled = table.get("enabled").and_then(|v| v.as_bool()).unwrap_or(true);
+				}
+
+				_ => ()
+			}
+		}
+
+		if let Some(value) = table.get("socket") {
+			match *value {
+				Value::String(ref value) =>
+					self.socket = Some(value.clone()),
+
+				Value::Boolean(true) =>
+					self.socket = Some("{runtime}/cancer-{pid}.sock".into()),
+
+				Value::Boolean(false) =>
+					self.socket = None,
+
+				_ => ()
+			}
+		}
+
 		if let Some(value) = table.get("cache") {
 			match *value {
 				Value::Integer(value) =>
@@ -99,10 +233,46 @@ impl Environment {
 		if let Some(value) = table.get("scroll") {
 			match *value {
 				Value::Integer(value) =>
-					self.scroll = value as usize,
+					self.scroll = History::Rows(value as usize),
 
 				Value::Boolean(false) =>
-					self.scroll = 0,
+					self.scroll = History::Rows(0),
+
+				Value::Boolean(true) =>
+					self.scroll = History::Unlimited,
+
+				Value::String(ref value) if value == "unlimited" =>
+					self.scroll = History::Unlimited,
+
+				Value::String(ref value) => {
+					if let Some(value) = bytes(value) {
+						self.scroll = History::Bytes(value);
+					}
+					else {
+						error!(target: "cancer::config", "[environment.scroll] unknown size: {}", value);
+					}
+				}
+
+				_ => ()
+			}
+		}
+
+		if let Some(value) = table.get("spill") {
+			match *value {
+				Value::Boolean(false) =>
+					self.spill = None,
+
+				Value::Boolean(true) =>
+					self.spill = Some(SPILL),
+
+				Value::String(ref value) => {
+					if let Some(value) = bytes(value) {
+						self.spill = Some(value);
+					}
+					else {
+						error!(target: "cancer::config", "[environment.spill] unknown size: {}", value);
+					}
+				}
 
 				_ => ()
 			}
@@ -145,14 +315,38 @@ impl Environment {
 		self.term.as_ref().map(AsRef::as_ref)
 	}
 
+	pub fn encoding(&self) -> Encoding {
+		self.encoding
+	}
+
+	pub fn answerback(&self) -> &str {
+		&self.answer
+	}
+
+	pub fn printer(&self) -> Option<&Printer> {
+		self.printer.as_ref()
+	}
+
+	pub fn log(&self) -> &Log {
+		&self.log
+	}
+
+	pub fn socket(&self) -> Option<&str> {
+		self.socket.as_ref().map(AsRef::as_ref)
+	}
+
 	pub fn cache(&self) -> usize {
 		self.cache
 	}
 
-	pub fn scroll(&self) -> usize {
+	pub fn scroll(&self) -> History {
 		self.scroll
 	}
 
+	pub fn spill(&self) -> Option<usize> {
+		self.spill
+	}
+
 	pub fn batch(&self) -> Option<u32> {
 		self.batch
 	}
@@ -176,8 +370,35 @@ impl X11 {
 	}
 }
 
+impl Log {
+	pub fn path(&self) -> &str {
+		&self.path
+	}
+
+	pub fn mode(&self) -> Logging {
+		self.mode
+	}
+
+	pub fn enabled(&self) -> bool {
+		self.enabled
+	}
+}
+
 impl Cocoa {
 	pub fn bell(&self) -> Option<&str> {
 		self.bell.as_ref().map(AsRef::as_ref)
 	}
 }
+
+/// Parse a size in bytes with an optional K, M or G suffix.
+fn bytes(value: &str) -> Option<usize> {
+	let value = value.trim().trim_right_matches(|c| c == 'B' || c == 'b');
+	let (number, unit) = match value.chars().last() {
+		Some('K') | Some('k') => (&value[.. value.len() - 1], 1024),
+		Some('M') | Some('m') => (&value[.. value.len() - 1], 1024 * 1024),
+		Some('G') | Some('g') => (&value[.. value.len() - 1], 1024 * 1024 * 1024),
+		_                     => (value, 1),
+	};
+
+	number.trim().parse::<usize>().ok().and_then(|n| n.checked_mul(unit))
+}
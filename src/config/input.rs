@@ -17,10 +17,12 @@
 
 use toml;
 use platform::{Key, key};
+use interface::Tab;
 
 #[derive(PartialEq, Clone, Debug)]
 pub struct Input {
 	prefix: Key,
+	tabs:   Vec<(Key, Tab)>,
 	mouse:  bool,
 	locale: Option<String>,
 }
@@ -29,6 +31,15 @@ impl Default for Input {
 	fn default() -> Self {
 		Input {
 			prefix: Key::new("a".to_string().into(), key::LOGO, Default::default()),
+			tabs:   vec![
+				(to_key("L-t"), Tab::Create),
+				(to_key("L-w"), Tab::Close),
+				(to_key("L-Right"), Tab::Next),
+				(to_key("L-Left"), Tab::Previous),
+				(to_key("L-S-Right"), Tab::Move(1)),
+				(to_key("L-S-Left"), Tab::Move(-1)),
+			],
+
 			mouse:  true,
 			locale: None,
 		}
@@ -41,6 +52,18 @@ impl Input {
 			self.prefix = to_key(value);
 		}
 
+		if let Some(table) = table.get("tab").and_then(|v| v.as_table()) {
+			for &(name, action) in &[("create", Tab::Create), ("close", Tab::Close),
+			                         ("next", Tab::Next), ("previous", Tab::Previous),
+			                         ("left", Tab::Move(-1)), ("right", Tab::Move(1))]
+			{
+				if let Some(value) = table.get(name).and_then(|v| v.as_str()) {
+					self.tabs.retain(|&(_, a)| a != action);
+					self.tabs.push((to_key(value), action));
+				}
+			}
+		}
+
 		if let Some(value) = table.get("mouse").and_then(|v| v.as_bool()) {
 			self.mouse = value;
 		}
@@ -54,6 +77,11 @@ impl Input {
 		&self.prefix
 	}
 
+	/// Get the tab action bound to the key, if any.
+	pub fn tab(&self, key: &Key) -> Option<Tab> {
+		self.tabs.iter().find(|&&(ref k, _)| k == key).map(|&(_, action)| action)
+	}
+
 	pub fn mouse(&self) -> bool {
 		self.mouse
 	}
@@ -63,7 +91,8 @@ impl Input {
 	}
 }
 
-fn to_key<T: AsRef<str>>(value: T) -> Key {
+/// Parse a key, optionally prefixed by `C-`, `A-`, `S-` and `L-` modifiers.
+pub fn to_key<T: AsRef<str>>(value: T) -> Key {
 	let     value     = value.as_ref();
 	let mut modifiers = value.split('-').collect::<Vec<&str>>();
 	let     button    = modifiers.pop().unwrap().to_lowercase();
@@ -24,12 +24,14 @@ use regex::Regex;
 use config::util::{to_color, to_attributes};
 use config::style::{Cursor, Shape};
 use style::{self, Style};
+use terminal::capture::Format;
 
 #[derive(Clone, Debug)]
 pub struct Overlay {
 	pub(super) cursor:    Cursor,
 	pub(super) status:    Option<Style>,
 	pub(super) selection: Style,
+	pub(super) capture:   Capture,
 
 	pub(super) hinter:  Hinter,
 	pub(super) hinters: HashMap<u32, Hinter, BuildHasherDefault<FnvHasher>>,
@@ -52,12 +54,30 @@ impl Default for Overlay {
 				attributes: style::NONE,
 			},
 
+			capture: Default::default(),
+
 			hinter:  Default::default(),
 			hinters: Default::default(),
 		}
 	}
 }
 
+/// Where and how the scroll back is saved.
+#[derive(Clone, Debug)]
+pub struct Capture {
+	path:   String,
+	format: Format,
+}
+
+impl Default for Capture {
+	fn default() -> Self {
+		Capture {
+			path:   "~/cancer.capture".into(),
+			format: Format::Text,
+		}
+	}
+}
+
 #[derive(Clone, Debug)]
 pub struct Hinter {
 	label:   Vec<char>,
@@ -153,6 +173,21 @@ impl Overlay {
 			}
 		}
 
+		if let Some(table) = table.get("capture").and_then(|v| v.as_table()) {
+			if let Some(value) = table.get("path").and_then(|v| v.as_str()) {
+				self.capture.path = value.into();
+			}
+
+			if let Some(value) = table.get("format").and_then(|v| v.as_str()) {
+				if let Some(value) = Format::parse(value) {
+					self.capture.format = value;
+				}
+				else {
+					error!(target: "cancer::config", "[overlay.capture.format] unknown format: {}", value);
+				}
+			}
+		}
+
 		if let Some(table) = table.get("hinter").and_then(|v| v.as_table()) {
 			if let Some(value) = table.get("label").and_then(|v| v.as_str()) {
 				self.hinter.label = value.chars().collect();
@@ -251,11 +286,25 @@ impl Overlay {
 		&self.selection
 	}
 
+	pub fn capture(&self) -> &Capture {
+		&self.capture
+	}
+
 	pub fn hinter(&self, id: u32) -> &Hinter {
 		self.hinters.get(&id).unwrap_or(&self.hinter)
 	}
 }
 
+impl Capture {
+	pub fn path(&self) -> &str {
+		&self.path
+	}
+
+	pub fn format(&self) -> Format {
+		self.format
+	}
+}
+
 impl Hinter {
 	pub fn label(&self) -> &[char] {
 		&self.label
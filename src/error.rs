@@ -31,6 +31,7 @@ pub enum Error {
 	Message(String),
 	Nul(ffi::NulError),
 	Directory(app_dirs::AppDirsError),
+	#[cfg(feature = "window")]
 	Platform(Platform),
 	Unknown,
 }
@@ -77,6 +78,7 @@ impl From<RecvError> for Error {
 	}
 }
 
+#[cfg(feature = "window")]
 #[derive(Debug)]
 pub enum Platform {
 	#[cfg(all(unix, not(target_os = "macos")))]
@@ -86,7 +88,7 @@ pub enum Platform {
 	Quartz(platform::quartz::Error),
 }
 
-#[cfg(all(unix, not(target_os = "macos")))]
+#[cfg(all(unix, not(target_os = "macos"), feature = "window"))]
 pub mod platform {
 	pub mod x11 {
 		use super::super::{Error as Err, Platform};
@@ -120,7 +122,7 @@ pub mod platform {
 	}
 }
 
-#[cfg(target_os = "macos")]
+#[cfg(all(target_os = "macos", feature = "window"))]
 pub mod platform {
 	pub mod quartz {
 		pub type Error = ();
@@ -151,7 +153,7 @@ impl error::Error for Error {
 			Error::Unknown =>
 				"Unknown error.",
 
-			#[cfg(all(unix, not(target_os = "macos")))]
+			#[cfg(all(unix, not(target_os = "macos"), feature = "window"))]
 			Error::Platform(Platform::X11(ref err)) => match *err {
 				platform::x11::Error::Request(..) =>
 					"An X request failed.",
@@ -166,7 +168,7 @@ impl error::Error for Error {
 					"Connection to the X display failed.",
 			},
 
-			#[cfg(target_os = "macos")]
+			#[cfg(all(target_os = "macos", feature = "window"))]
 			Error::Platform(Platform::Quartz(_)) =>
 				"Something happened :(",
 		}
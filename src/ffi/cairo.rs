@@ -16,7 +16,7 @@
 // along with cancer.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::os::raw::c_void;
-use libc::{c_int, c_uchar, c_double};
+use libc::{c_int, c_char, c_uchar, c_double};
 
 #[repr(C)]
 pub struct cairo_t(c_void);
@@ -93,6 +93,7 @@ extern "C" {
 	pub fn cairo_format_stride_for_width(format: cairo_format_t, width: c_int) -> c_int;
 
 	pub fn cairo_matrix_init_translate(matrix: *mut cairo_matrix_t, x: c_double, y: c_double);
+	pub fn cairo_matrix_init(matrix: *mut cairo_matrix_t, xx: c_double, yx: c_double, xy: c_double, yy: c_double, x0: c_double, y0: c_double);
 
 	pub fn cairo_create(surface: *mut cairo_surface_t) -> *mut cairo_t;
 	pub fn cairo_destroy(cr: *mut cairo_t);
@@ -115,11 +116,22 @@ extern "C" {
 
 	pub fn cairo_move_to(cr: *mut cairo_t, x: c_double, y: c_double);
 	pub fn cairo_line_to(cr: *mut cairo_t, x: c_double, y: c_double);
+	pub fn cairo_curve_to(cr: *mut cairo_t, x1: c_double, y1: c_double, x2: c_double, y2: c_double, x3: c_double, y3: c_double);
+	pub fn cairo_arc(cr: *mut cairo_t, xc: c_double, yc: c_double, radius: c_double, angle1: c_double, angle2: c_double);
+	pub fn cairo_arc_negative(cr: *mut cairo_t, xc: c_double, yc: c_double, radius: c_double, angle1: c_double, angle2: c_double);
+	pub fn cairo_new_path(cr: *mut cairo_t);
+	pub fn cairo_get_current_point(cr: *mut cairo_t, x: *mut c_double, y: *mut c_double);
 	pub fn cairo_set_line_width(cr: *mut cairo_t, w: c_double);
 
+	pub fn cairo_set_font_size(cr: *mut cairo_t, size: c_double);
+	pub fn cairo_show_text(cr: *mut cairo_t, utf8: *const c_char);
+
 	pub fn cairo_clip(cr: *mut cairo_t);
 	pub fn cairo_rectangle(cr: *mut cairo_t, x: c_double, y: c_double, w: c_double, h: c_double);
 
+	pub fn cairo_image_surface_create(format: cairo_format_t, width: c_int, height: c_int) -> *mut cairo_surface_t;
+	pub fn cairo_image_surface_get_data(surface: *mut cairo_surface_t) -> *mut c_uchar;
+	pub fn cairo_image_surface_get_stride(surface: *mut cairo_surface_t) -> c_int;
 	pub fn cairo_image_surface_create_for_data(data: *const c_uchar, format: cairo_format_t, width: c_int, height: c_int, stride: c_int) -> *mut cairo_surface_t;
 	pub fn cairo_surface_flush(surface: *mut cairo_surface_t);
 	pub fn cairo_surface_destroy(surface: *mut cairo_surface_t);
@@ -129,7 +141,7 @@ extern "C" {
 	pub fn cairo_pattern_destroy(pattern: *mut cairo_pattern_t);
 }
 
-#[cfg(all(unix, not(target_os = "macos")))]
+#[cfg(all(unix, not(target_os = "macos"), feature = "window"))]
 pub mod platform {
 	use super::*;
 
@@ -143,7 +155,7 @@ pub mod platform {
 	}
 }
 
-#[cfg(target_os = "macos")]
+#[cfg(all(target_os = "macos", feature = "window"))]
 pub mod platform {
 	use super::*;
 
@@ -0,0 +1,68 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+use platform::Clipboard;
+
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub enum Action {
+	Urgent,
+	Overlay(bool),
+	Title(String),
+	Resize(u32, u32),
+	Copy(Clipboard, String),
+	CopyHtml(Clipboard, String, String),
+	Paste(Clipboard),
+	Open(Option<String>, String),
+	Tty(u32, u32),
+	Replay(Replay),
+	Tab(Tab),
+}
+
+/// Control over a replayed session.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Replay {
+	/// Pause or resume playback.
+	Pause,
+
+	/// Play the given number of chunks while paused.
+	Step(u32),
+
+	/// Double the speed.
+	Faster,
+
+	/// Halve the speed.
+	Slower,
+}
+
+/// Management of the tabs in a window.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Tab {
+	/// Open a new tab after the current one.
+	Create,
+
+	/// Close the current tab.
+	Close,
+
+	/// Switch to the next tab.
+	Next,
+
+	/// Switch to the previous tab.
+	Previous,
+
+	/// Move the current tab by the given number of places.
+	Move(i32),
+}
@@ -22,28 +22,19 @@ use std::vec;
 use picto::Region;
 use error;
 use config::Config;
-use platform::{Clipboard, Key, Mouse};
-use terminal::{Terminal, Mode, Iter, Cell};
-use terminal::{cursor, touched};
+use platform::{Key, Mouse};
+use terminal::{Terminal, Mode, Iter, Cell, Soft};
+use terminal::{cursor, touched, image, bidi};
 use overlay::Overlay;
 
+pub use cancer::interface::{Action, Replay, Tab};
+
 #[derive(Debug)]
 pub enum Interface {
 	Terminal(Terminal),
 	Overlay(Overlay),
 }
 
-#[derive(Eq, PartialEq, Clone, Debug)]
-pub enum Action {
-	Urgent,
-	Overlay(bool),
-	Title(String),
-	Resize(u32, u32),
-	Copy(Clipboard, String),
-	Paste(Clipboard),
-	Open(Option<String>, String),
-}
-
 impl Interface {
 	pub fn config(&self) -> &Config {
 		match *self {
@@ -55,6 +46,16 @@ impl Interface {
 		}
 	}
 
+	pub fn terminal(&self) -> &Terminal {
+		match *self {
+			Interface::Terminal(ref terminal) =>
+				terminal,
+
+			Interface::Overlay(ref overlay) =>
+				overlay,
+		}
+	}
+
 	pub fn overlay(&self) -> bool {
 		if let Interface::Terminal(..) = *self {
 			false
@@ -124,6 +125,36 @@ impl Interface {
 		}
 	}
 
+	pub fn soft(&self) -> &Soft {
+		match *self {
+			Interface::Terminal(ref terminal) =>
+				terminal.soft(),
+
+			Interface::Overlay(ref overlay) =>
+				overlay.soft(),
+		}
+	}
+
+	pub fn placed(&self, x: u32, y: u32) -> Vec<image::Placed> {
+		match *self {
+			Interface::Terminal(ref terminal) =>
+				terminal.placed(x, y),
+
+			Interface::Overlay(ref overlay) =>
+				overlay.placed(x, y),
+		}
+	}
+
+	pub fn bidi(&self, y: u32) -> Option<bidi::Map> {
+		match *self {
+			Interface::Terminal(ref terminal) =>
+				terminal.bidi(y),
+
+			Interface::Overlay(ref overlay) =>
+				overlay.bidi(y),
+		}
+	}
+
 	pub fn resize(&mut self, width: u32, height: u32) {
 		match *self {
 			Interface::Terminal(ref mut terminal) =>
@@ -173,6 +204,10 @@ impl Interface {
 			return Ok((vec![Action::Overlay(!self.overlay())].into_iter(), touched::Iter::empty()));
 		}
 
+		if let Some(action) = self.config().input().tab(&key) {
+			return Ok((vec![Action::Tab(action)].into_iter(), touched::Iter::empty()));
+		}
+
 		match *self {
 			Interface::Terminal(ref mut terminal) => {
 				try!(terminal.key(key, output));
@@ -0,0 +1,110 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The terminal core.
+//!
+//! A `Terminal` takes the output of a program and keeps the resulting `Grid`
+//! of `Cell`s, it doesn't need a window and can be driven headless:
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use cancer::{Config, Terminal};
+//!
+//! let mut terminal = Terminal::new(Arc::new(Config::default()), (8, 16), (80, 24)).unwrap();
+//! let mut replies  = Vec::new();
+//!
+//! terminal.input("\x1B[1mhello\x1B[0m", &mut replies).unwrap();
+//! assert_eq!(terminal.grid()[(0, 0)].value(), "h");
+//! ```
+//!
+//! Image cells (sixel, ReGIS, kitty and iTerm2 graphics) are drawn through
+//! cairo and need the `images` feature, without it those controls are
+//! consumed and ignored.
+
+#![feature(conservative_impl_trait, slice_patterns)]
+#![feature(type_ascription, inclusive_range_syntax)]
+#![feature(box_syntax, try_from)]
+
+#[macro_use(error, debug, log)]
+extern crate log;
+
+#[macro_use]
+extern crate bitflags;
+extern crate bit_vec;
+extern crate fnv;
+extern crate itertools;
+extern crate picto;
+extern crate control_code as control;
+extern crate base64;
+extern crate flate2;
+
+extern crate unicode_segmentation;
+extern crate unicode_width;
+extern crate unicode_bidi;
+extern crate tendril;
+
+extern crate regex;
+extern crate app_dirs;
+extern crate toml;
+
+extern crate libc;
+
+#[cfg(all(unix, not(target_os = "macos"), feature = "window"))]
+pub extern crate xcb;
+#[cfg(all(target_os = "macos", feature = "window"))]
+extern crate core_graphics;
+
+#[macro_use]
+#[doc(hidden)]
+pub mod util;
+pub mod error;
+
+#[cfg(feature = "images")]
+#[doc(hidden)]
+pub mod ffi;
+#[cfg(feature = "images")]
+pub mod sys;
+
+pub mod config;
+pub use config::Config;
+
+pub mod style;
+pub use style::Style;
+
+pub mod platform {
+	pub mod key;
+	pub use self::key::Key;
+
+	pub mod mouse;
+	pub use self::mouse::Mouse;
+
+	mod clipboard;
+	pub use self::clipboard::Clipboard;
+}
+
+pub mod interface {
+	mod action;
+	pub use self::action::{Action, Replay, Tab};
+}
+
+pub mod overlay {
+	mod status;
+	pub use self::status::Status;
+}
+
+pub mod terminal;
+pub use terminal::{Terminal, Grid, Cell};
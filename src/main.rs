@@ -38,9 +38,12 @@ extern crate shlex;
 extern crate schedule_recv as timer;
 extern crate picto;
 extern crate control_code as control;
+extern crate base64;
+extern crate flate2;
 
 extern crate unicode_segmentation;
 extern crate unicode_width;
+extern crate unicode_bidi;
 extern crate tendril;
 
 extern crate regex;
@@ -69,21 +72,18 @@ pub extern crate core_foundation;
 pub extern crate core_graphics;
 
 #[macro_use]
-mod util;
-mod error;
-mod ffi;
-mod sys;
+extern crate cancer;
+use cancer::{error, ffi, sys, config, style, terminal, util};
 
-mod config;
 mod font;
-mod style;
 
 mod platform;
 mod renderer;
 
 mod interface;
-mod terminal;
 mod overlay;
+mod cast;
+mod tabs;
 
 #[cfg(not(feature = "fuzzy"))]
 fn main() {
@@ -93,15 +93,20 @@ fn main() {
 	use std::mem;
 	use std::io::Write;
 	use std::thread;
+	use std::env;
 
 	use picto::Region;
 	use config::Config;
 	use font::Font;
 	use renderer::Renderer;
-	use interface::{Interface, Action};
+	use interface::{Interface, Action, Tab};
 	use terminal::Terminal;
 	use overlay::Overlay;
-	use platform::{Window, Tty, Event, Proxy};
+	use tabs::{self, Tabs};
+	use cast::{Recorder, Player};
+	use platform::{Window, Tty, Source, Event, Proxy};
+	use platform::socket::{self, Socket, Request, Reply};
+	use terminal::capture;
 	use platform::mouse::{self, Mouse};
 
 	env_logger::init();
@@ -143,6 +148,36 @@ fn main() {
 			.long("title")
 			.takes_value(true)
 			.help("Specify the window title."))
+		.arg(Arg::with_name("record")
+			.long("record")
+			.takes_value(true)
+			.help("Record the session as asciicast to the given file."))
+		.arg(Arg::with_name("replay")
+			.long("replay")
+			.takes_value(true)
+			.conflicts_with("execute")
+			.help("Replay an asciicast recording instead of running a program."))
+		.arg(Arg::with_name("speed")
+			.long("speed")
+			.takes_value(true)
+			.requires("replay")
+			.help("Speed of the replay."))
+		.arg(Arg::with_name("dump")
+			.long("dump")
+			.takes_value(true)
+			.conflicts_with_all(&["execute", "replay"])
+			.help("Feed the given file, or - for stdin, to a terminal without a window and print its contents."))
+		.arg(Arg::with_name("format")
+			.long("format")
+			.takes_value(true)
+			.possible_values(&["text", "ansi", "html", "rtf"])
+			.requires("dump")
+			.help("Format of the dump, colors and attributes are kept except in text."))
+		.arg(Arg::with_name("size")
+			.long("size")
+			.takes_value(true)
+			.requires("dump")
+			.help("Size of the terminal for the dump, as COLUMNSxROWS."))
 		.arg(Arg::with_name("tic")
 			.short("T")
 			.long("tic")
@@ -155,6 +190,12 @@ fn main() {
 	}
 
 	let config = Arc::new(Config::load(matches.value_of("config")).unwrap());
+
+	if let Some(path) = matches.value_of("dump") {
+		dump(&matches, path, config).unwrap();
+		return;
+	}
+
 	let font   = Arc::new(Font::load(matches.value_of("font").unwrap_or(config.style().font())).unwrap());
 
 	let mut window = Window::new(matches.value_of("name"), config.clone(), font.clone()).unwrap();
@@ -166,6 +207,39 @@ fn main() {
 
 	let _ = window.run(spawn(&matches, config.clone(), font.clone(), proxy).unwrap());
 
+	fn dump(matches: &ArgMatches, path: &str, config: Arc<Config>) -> error::Result<()> {
+		use std::fs::File;
+		use std::io::{self, Read};
+		use terminal::capture::{self, Format};
+
+		let mut size = matches.value_of("size").unwrap_or("80x24").split('x').map(|v| v.parse::<u32>());
+		let (columns, rows) = match (size.next(), size.next()) {
+			(Some(Ok(columns)), Some(Ok(rows))) if columns > 0 && rows > 0 =>
+				(columns, rows),
+
+			_ =>
+				return Err(format!("invalid size: {}", matches.value_of("size").unwrap()).into()),
+		};
+
+		let mut input = Vec::new();
+		if path == "-" {
+			io::stdin().read_to_end(&mut input)?;
+		}
+		else {
+			File::open(path)?.read_to_end(&mut input)?;
+		}
+
+		// There's no font, the cell size only matters for images.
+		let mut terminal = Terminal::new(config, (8, 16), (columns, rows))?;
+		terminal.input(&input, io::sink())?;
+
+		let format = matches.value_of("format").and_then(Format::parse).unwrap_or(Format::Text);
+		let stdout = io::stdout();
+		capture::write(terminal.config(), terminal.grid(), i64::min_value(), i64::max_value(), format, stdout.lock())?;
+
+		Ok(())
+	}
+
 	fn spawn<W: platform::Proxy + 'static>(matches: &ArgMatches, config: Arc<Config>, font: Arc<Font>, mut window: W) -> error::Result<Sender<Event>> {
 		let (sender, events) = channel();
 		window.prepare(sender.clone());
@@ -179,11 +253,51 @@ fn main() {
 			(font.width(), font.height() + config.style().spacing()),
 			(renderer.columns(), renderer.rows()))?);
 
-		let mut tty = Tty::spawn(
-			matches.value_of("term").or_else(|| config.environment().term()),
-			matches.value_of("execute").or_else(|| config.environment().program()),
-			(font.width(), font.height() + config.style().spacing()),
-			(renderer.columns(), renderer.rows()))?;
+		// The socket path is exported to the program, unless there's none.
+		let socket = if let Some(path) = Socket::path(&config) {
+			match Socket::bind(&path, sender.clone()) {
+				Ok(socket) => {
+					env::set_var("CANCER_SOCKET", &path);
+					Some(socket)
+				}
+
+				Err(err) => {
+					error!(target: "cancer::socket", "could not listen on {}: {}", path.display(), err);
+					env::remove_var("CANCER_SOCKET");
+					None
+				}
+			}
+		}
+		else {
+			env::remove_var("CANCER_SOCKET");
+			None
+		};
+
+		let (_resizer, mut resizes) = channel();
+		let mut player              = None;
+
+		let mut tty: Box<Source> = if let Some(path) = matches.value_of("replay") {
+			let mut replay = Player::open(path, matches.value_of("speed").and_then(|v| v.parse().ok()).unwrap_or(1.0))?;
+			resizes = replay.resizes();
+			player  = Some(replay.controls());
+
+			Box::new(replay)
+		}
+		else {
+			Box::new(Tty::spawn(
+				matches.value_of("term").or_else(|| config.environment().term()),
+				matches.value_of("execute").or_else(|| config.environment().program()),
+				(font.width(), font.height() + config.style().spacing()),
+				(renderer.columns(), renderer.rows()))?)
+		};
+
+		let mut recorder = if let Some(path) = matches.value_of("record") {
+			Some(Recorder::create(path, (renderer.columns(), renderer.rows()),
+				matches.value_of("term").or_else(|| config.environment().term()))?)
+		}
+		else {
+			None
+		};
 
 		let mut focused = true;
 		let mut visible = true;
@@ -195,7 +309,81 @@ fn main() {
 		let mut batching          = None;
 		let mut batched           = None;
 
-		let input = tty.output();
+		// The output of every tab ends up here, tagged with the tab identifier.
+		let (outputs, input) = channel();
+		tabs::forward(0, tty.output(), outputs.clone());
+
+		let mut tabs    = Tabs::new(matches.value_of("title").unwrap_or("cancer").into());
+		let     term    = matches.value_of("term").or_else(|| config.environment().term()).map(String::from);
+		let     program = matches.value_of("execute").or_else(|| config.environment().program()).map(String::from);
+
+		macro_rules! open {
+			() => ({
+				let cell = (font.width(), font.height() + config.style().spacing());
+				let size = (renderer.columns(), renderer.rows());
+
+				Terminal::new(config.clone(), cell, size).and_then(|terminal|
+					Tty::spawn(term.as_ref().map(AsRef::as_ref), program.as_ref().map(AsRef::as_ref), cell, size).map(|tty|
+						(Interface::from(terminal), Box::new(tty) as Box<Source>)))
+			});
+		}
+
+		macro_rules! bar {
+			() => ({
+				if visible && tabs.len() > 1 {
+					window.render(&mut surface, ||
+						renderer.tabs(&tabs));
+				}
+			});
+		}
+
+		macro_rules! redraw {
+			() => ({
+				let width   = renderer.width();
+				let height  = renderer.height();
+				let rows    = renderer.rows();
+				let columns = renderer.columns();
+
+				window.render(&mut surface, ||
+					renderer.render(render!(options), Some(Region::from(0, 0, width, height)),
+						&interface, Region::from(0, 0, columns, rows).absolute()));
+
+				bar!();
+			});
+		}
+
+		macro_rules! resize {
+			() => ({
+				let rows    = renderer.rows();
+				let columns = renderer.columns();
+
+				if interface.columns() != columns || interface.region().height != rows {
+					if interface.overlay() {
+						interface = try!(return interface.into_inner(tty.by_ref())).into();
+					}
+
+					interface.resize(columns, rows);
+					try!(return tty.resize(interface.columns(), interface.rows()));
+
+					if let Some(recorder) = recorder.as_mut() {
+						recorder.resize(interface.columns(), interface.rows());
+					}
+				}
+
+				try!(return tabs.resize(columns, rows));
+			});
+		}
+
+		macro_rules! switched {
+			() => ({
+				if renderer.show_bar(&surface, tabs.len() > 1) {
+					resize!();
+				}
+
+				window.set_title(tabs.current().title().into());
+				redraw!();
+			});
+		}
 
 		macro_rules! render {
 			(options) => ({
@@ -247,11 +435,13 @@ fn main() {
 						}
 
 						Action::Title(string) => {
+							tabs.set_title(string.clone());
 							window.set_title(string);
+							bar!();
 						}
 
 						Action::Resize(columns, rows) => {
-							let (width, height) = Renderer::dimensions(columns, rows, &config, &font);
+							let (width, height) = renderer.size(columns, rows);
 							window.resize(width, height);
 						}
 
@@ -259,6 +449,10 @@ fn main() {
 							window.copy(name, value);
 						}
 
+						Action::CopyHtml(name, value, html) => {
+							window.copy_html(name, value, html);
+						}
+
 						Action::Paste(name) => {
 							window.paste(name)
 						}
@@ -266,6 +460,58 @@ fn main() {
 						Action::Open(through, what) => {
 							window.open(through.as_ref().map(AsRef::as_ref), what.as_ref()).unwrap();
 						}
+
+						Action::Tty(columns, rows) => {
+							try!(return tty.resize(columns, rows));
+
+							if let Some(recorder) = recorder.as_mut() {
+								recorder.resize(columns, rows);
+							}
+						}
+
+						Action::Replay(control) => {
+							if let Some(player) = player.as_ref() {
+								let _ = player.send(control);
+							}
+						}
+
+						Action::Tab(Tab::Create) => {
+							if renderer.show_bar(&surface, true) {
+								resize!();
+							}
+
+							let (terminal, mut source) = try!(return open!());
+							let output                 = source.output();
+							let id                     = tabs.create(&mut interface, &mut tty, (terminal, source));
+
+							tabs::forward(id, output, outputs.clone());
+							switched!();
+						}
+
+						Action::Tab(Tab::Close) => {
+							let id = tabs.current().id();
+
+							if !tabs.close(id, &mut interface, &mut tty) {
+								return;
+							}
+
+							switched!();
+						}
+
+						Action::Tab(Tab::Next) => {
+							tabs.next(&mut interface, &mut tty);
+							switched!();
+						}
+
+						Action::Tab(Tab::Previous) => {
+							tabs.previous(&mut interface, &mut tty);
+							switched!();
+						}
+
+						Action::Tab(Tab::Move(by)) => {
+							tabs.shift(by);
+							bar!();
+						}
 					}
 				}
 
@@ -284,6 +530,8 @@ fn main() {
 
 		thread::Builder::new().name("cancer::runner".into()).spawn(move || {
 			let _batcher = _batcher;
+			let _resizer = _resizer;
+			let _socket  = socket;
 
 			loop {
 				match batching.take() {
@@ -328,14 +576,7 @@ fn main() {
 							}
 
 							Event::Redraw => {
-								let width   = renderer.width();
-								let height  = renderer.height();
-								let rows    = renderer.rows();
-								let columns = renderer.columns();
-
-								window.render(&mut surface, ||
-									renderer.render(render!(options), Some(Region::from(0, 0, width, height)),
-										&interface, Region::from(0, 0, columns, rows).absolute()));
+								redraw!();
 							}
 
 							Event::Damaged(region) => {
@@ -343,6 +584,10 @@ fn main() {
 
 								window.render(&mut surface, ||
 									renderer.render(render!(options), Some(region), &interface, damaged.relative()));
+
+								if region.y < renderer.bar() {
+									bar!();
+								}
 							}
 
 							Event::Focus(focus) => {
@@ -362,14 +607,73 @@ fn main() {
 
 								surface = window.surface().unwrap();
 								renderer.resize(&surface, width, height);
+								resize!();
+							}
 
-								let rows    = renderer.rows();
-								let columns = renderer.columns();
+							Event::Control(control) => {
+								let reply = match *control.request() {
+									Request::SendText(ref text) => {
+										try!(return tty.write_all(text.as_bytes()));
+										try!(return tty.flush());
 
-								if interface.columns() != columns || interface.rows() != rows {
-									try!(return tty.resize(columns, rows));
-									interface.resize(columns, rows);
-								}
+										Reply::Ok
+									}
+
+									Request::GetText(start, end, format) => {
+										let terminal = interface.terminal();
+										Reply::Value(capture::capture(terminal.config(), terminal.grid(), start, end, format))
+									}
+
+									Request::GetCursor => {
+										let (x, y) = interface.terminal().cursor().position();
+										Reply::Value(format!("{} {}", x, y))
+									}
+
+									Request::GetModes => {
+										Reply::Value(format!("{:?}", interface.terminal().mode()))
+									}
+
+									Request::SetTitle(ref title) => {
+										tabs.set_title(title.clone());
+										window.set_title(title.clone());
+										bar!();
+
+										Reply::Ok
+									}
+
+									Request::Resize(columns, rows) => {
+										let (width, height) = renderer.size(columns, rows);
+										window.resize(width, height);
+
+										Reply::Ok
+									}
+
+									Request::Overlay(socket::Overlay::Leave) => {
+										if interface.overlay() {
+											interface = try!(return interface.into_inner(tty.by_ref())).into();
+											render!(interface.region().absolute());
+										}
+
+										Reply::Ok
+									}
+
+									Request::Overlay(ref what) => {
+										if !interface.overlay() {
+											interface = Overlay::new(try!(return interface.into_inner(tty.by_ref()))).into();
+											render!(interface.region().absolute());
+										}
+
+										if let socket::Overlay::Press(ref keys) = *what {
+											for key in keys {
+												render!(handle interface.key(key.clone(), tty.by_ref()));
+											}
+										}
+
+										Reply::Ok
+									}
+								};
+
+								control.reply(reply);
 							}
 
 							Event::Paste(value) => {
@@ -401,7 +705,60 @@ fn main() {
 					},
 
 					input = input.recv() => {
-						render!(handle interface.input(&try!(return input), tty.by_ref()));
+						let (id, input) = try!(return input);
+
+						// The program is gone, tabs closed from the bar are already gone.
+						let input = if let Some(input) = input {
+							input
+						}
+						else {
+							if tabs.iter().any(|t| t.id() == id) {
+								if !tabs.close(id, &mut interface, &mut tty) {
+									return;
+								}
+
+								switched!();
+							}
+
+							continue;
+						};
+
+						if id != tabs.current().id() {
+							let (changed, actions) = try!(continue tabs.input(id, &input));
+
+							for action in actions {
+								match action {
+									Action::Copy(name, value) =>
+										window.copy(name, value),
+
+									Action::CopyHtml(name, value, html) =>
+										window.copy_html(name, value, html),
+
+									_ => ()
+								}
+							}
+
+							if changed {
+								bar!();
+							}
+
+							continue;
+						}
+
+						// Only the first tab is recorded.
+						if id == 0 {
+							if let Some(recorder) = recorder.as_mut() {
+								recorder.output(&input);
+							}
+						}
+
+						render!(handle interface.input(&input, tty.by_ref()));
+					}
+
+					size = resizes.recv() => {
+						let (columns, rows) = try!(return size);
+						let (width, height) = renderer.size(columns, rows);
+						window.resize(width, height);
 					}
 				}
 			}
@@ -453,3 +810,4 @@ fn main() {
 		});
 	}
 }
+
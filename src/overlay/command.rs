@@ -16,6 +16,7 @@
 // along with cancer.  If not, see <http://www.gnu.org/licenses/>.
 
 use platform::Clipboard;
+use interface::Replay;
 
 pub enum Command {
 	None,
@@ -26,6 +27,11 @@ pub enum Command {
 	Copy(Clipboard),
 	Paste(Clipboard),
 	Hint(Hint),
+	Encoding,
+	Clear,
+	Log,
+	Capture(Capture),
+	Replay(Replay),
 }
 
 pub enum Scroll {
@@ -78,6 +84,11 @@ pub enum Select {
 	Line,
 }
 
+pub enum Capture {
+	Copy(Clipboard),
+	Save,
+}
+
 pub enum Hint {
 	Start(u32),
 	Pick(char),
@@ -18,8 +18,7 @@
 mod command;
 pub use self::command::Command;
 
-mod status;
-pub use self::status::Status;
+pub use cancer::overlay::Status;
 
 mod overlay;
 pub use self::overlay::Overlay;
@@ -34,10 +34,14 @@ use terminal::{Terminal, Cursor, Iter, Row};
 use terminal::touched::{self, Touched};
 use terminal::cell::{self, Cell};
 use terminal::cursor;
+use terminal::image;
+use terminal::bidi;
+use terminal::capture;
+use terminal::mode;
 use overlay::Status;
 use overlay::command::{self, Command};
 use overlay::hints::{Hint, Hints};
-use interface::Action;
+use interface::{Action, Replay};
 
 #[derive(Debug)]
 pub struct Overlay {
@@ -49,6 +53,7 @@ pub struct Overlay {
 	cursor: Cursor,
 	view:   HashMap<(u32, u32), Cell, BuildHasherDefault<FnvHasher>>,
 	status: Option<Status>,
+	blank:  Row,
 
 	prefix: Option<u8>,
 	times:  Option<u32>,
@@ -179,6 +184,10 @@ impl Overlay {
 			let mut status = Status::new(*c, inner.columns());
 			status.mode("NORMAL");
 
+			if inner.is_logging() {
+				status.flags("LOG");
+			}
+
 			let (x, y) = cursor.position();
 			let y      = inner.grid().back().len() as u32 + y + 2;
 			status.position((x, y));
@@ -202,6 +211,8 @@ impl Overlay {
 			hinted:  Rc::new(Style::default()),
 		};
 
+		let blank = Row::empty(inner.columns() as usize);
+
 		Overlay {
 			inner:   inner,
 			touched: Touched::default(),
@@ -211,6 +222,7 @@ impl Overlay {
 			cursor: cursor,
 			view:   Default::default(),
 			status: status,
+			blank:  blank,
 
 			prefix: None,
 			times:  None,
@@ -233,6 +245,58 @@ impl Overlay {
 		cursor::Cell::new(&self.cursor, cell::Position::new(x, y, &self[(x, y)]))
 	}
 
+	/// Get the images placed over the given cell.
+	pub fn placed(&self, x: u32, y: u32) -> Vec<image::Placed> {
+		if self.inner.images().is_empty() {
+			return Vec::new();
+		}
+
+		if y >= self.inner.rows() || (self.status.is_some() && y == self.inner.rows() - 1) {
+			return Vec::new();
+		}
+
+		let view = self.inner.grid().view();
+
+		// Same as the indexing, the offset starts from the bottom.
+		let mut offset = (view.len() as u32 - 1 - y) + self.scroll;
+
+		if self.status.is_some() {
+			offset -= 1;
+		}
+
+		let line = self.inner.grid().scrolled() + view.len() as i64 - 1 - offset as i64;
+		self.inner.images().at(x, y, line)
+	}
+
+	/// Get the display order of the given row, if it's reordered.
+	pub fn bidi(&self, y: u32) -> Option<bidi::Map> {
+		if !self.inner.mode().contains(mode::BIDI) || y >= self.inner.rows() ||
+		   (self.status.is_some() && y == self.inner.rows() - 1)
+		{
+			return None;
+		}
+
+		bidi::Map::new(self.inner.columns(), self.inner.mode().contains(mode::RIGHT_TO_LEFT),
+			|x| &self[(x, y)])
+	}
+
+	/// Get the display order of the row at the given internal Y coordinate.
+	fn order(&self, y: u32) -> Option<bidi::Map> {
+		if !self.inner.mode().contains(mode::BIDI) {
+			return None;
+		}
+
+		let row = &self[y];
+		bidi::Map::new(self.inner.columns(), self.inner.mode().contains(mode::RIGHT_TO_LEFT),
+			|x| &row[x as usize])
+	}
+
+	/// Turn a logical position at the given internal Y coordinate to the
+	/// column it's displayed at, selections are kept in display order.
+	fn visual(&self, (x, y): (u32, u32)) -> (u32, u32) {
+		(self.order(y).map(|m| m.visual(x)).unwrap_or(x), y)
+	}
+
 	/// Get an iterator over positioned cells.
 	pub fn iter<T: Iterator<Item = (u32, u32)>>(&self, iter: T) -> Iter<Self, T> {
 		Iter::new(self, iter)
@@ -422,6 +486,40 @@ impl Overlay {
 						_       => Clipboard::default(),
 					}.into()),
 
+				// Terminal settings.
+				"E" if key.modifier() == key::SHIFT =>
+					Command::Encoding,
+
+				"X" if key.modifier() == key::SHIFT =>
+					Command::Clear,
+
+				"L" if key.modifier() == key::SHIFT =>
+					Command::Log,
+
+				// Scroll back captures.
+				"Y" if key.modifier() == key::SHIFT =>
+					Command::Capture(command::Capture::Copy(match times {
+						Some(1) => Clipboard::Primary,
+						Some(2) => Clipboard::Secondary,
+						_       => Clipboard::default(),
+					})),
+
+				"S" if key.modifier() == key::SHIFT =>
+					Command::Capture(command::Capture::Save),
+
+				// Replay controls.
+				"P" if key.modifier() == key::SHIFT =>
+					Command::Replay(Replay::Pause),
+
+				"n" if key.modifier().is_empty() =>
+					Command::Replay(Replay::Step(times.unwrap_or(1))),
+
+				"+" =>
+					Command::Replay(Replay::Faster),
+
+				"-" =>
+					Command::Replay(Replay::Slower),
+
 				// Prefix setters.
 				"g" if key.modifier().is_empty() => {
 					self.prefix = Some(b'g');
@@ -551,8 +649,10 @@ impl Overlay {
 				Command::Scroll(command::Scroll::Down(1)),
 
 			// Cursor movement commands.
-			Mouse::Click(mouse::Click { button: mouse::Button::Left, press: false, position, .. }) =>
-				Command::Move(command::Move::To(position.x, position.y)),
+			Mouse::Click(mouse::Click { button: mouse::Button::Left, press: false, position, .. }) => {
+				let x = self.bidi(position.y).map(|m| m.logical(position.x)).unwrap_or(position.x);
+				Command::Move(command::Move::To(x, position.y))
+			}
 
 			// Selection commands.
 			Mouse::Click(mouse::Click { button: mouse::Button::Middle, press: false, .. }) =>
@@ -574,6 +674,14 @@ impl Overlay {
 
 	/// Handle a command.
 	fn handle(&mut self, command: Command) -> Vec<Action> {
+		// Scroll back rows are inflated again as they come into view.
+		self.inner.deflate();
+		self.inflate_view();
+
+		if let Some(selection) = self.selector.current {
+			self.inflate_selection(&selection);
+		}
+
 		let     before  = overlay!(self; cursor absolute);
 		let mut actions = self.command(command);
 		let     after   = overlay!(self; cursor absolute);
@@ -582,6 +690,7 @@ impl Overlay {
 			if self.selector.current.is_some() {
 				let s = self.selector.current.unwrap();
 				self.highlight(Highlight::Selection(&s), false);
+				let (before, after) = (self.visual(before), self.visual(after));
 				self.select(before, after);
 				let s = self.selector.current.unwrap();
 				self.highlight(Highlight::Selection(&s), true);
@@ -718,12 +827,31 @@ impl Overlay {
 
 			Command::Move(command::Move::Left(times)) => {
 				for _ in 0 ..times {
-					if overlay!(self; cursor Left(1)).is_some() {
+					// Reordered rows are walked in display order.
+					let (x, y) = overlay!(self; cursor);
+					let edge   = if let Some(map) = self.bidi(y) {
+						let column = map.visual(x);
+
+						if column > 0 {
+							overlay!(self; cursor Position(Some(map.logical(column - 1)), None));
+						}
+
+						column == 0
+					}
+					else {
+						overlay!(self; cursor Left(1)).is_some()
+					};
+
+					if edge {
 						if overlay!(self; cursor Up(1)).is_some() {
 							self.command(Command::Scroll(command::Scroll::Up(1)));
 						}
 
-						overlay!(self; cursor Position(Some(self.inner.columns() - 1), None));
+						let (_, y) = overlay!(self; cursor);
+						let last   = self.inner.columns() - 1;
+						let x      = self.bidi(y).map(|m| m.logical(last)).unwrap_or(last);
+
+						overlay!(self; cursor Position(Some(x), None));
 					}
 				}
 			}
@@ -746,13 +874,31 @@ impl Overlay {
 
 			Command::Move(command::Move::Right(times)) => {
 				for _ in 0 .. times {
-					if overlay!(self; cursor Right(1)).is_some() {
+					// Reordered rows are walked in display order.
+					let (x, y) = overlay!(self; cursor);
+					let edge   = if let Some(map) = self.bidi(y) {
+						let column = map.visual(x) + self[(x, y)].width();
+
+						if column < self.inner.columns() {
+							overlay!(self; cursor Position(Some(map.logical(column)), None));
+						}
+
+						column >= self.inner.columns()
+					}
+					else {
+						overlay!(self; cursor Right(1)).is_some()
+					};
+
+					if edge {
 						if overlay!(self; cursor Down(1)).is_some() {
 							self.command(Command::Scroll(command::Scroll::Down(1)));
 						}
 
 						if !self.at_end() {
-							overlay!(self; cursor Position(Some(0), None));
+							let (_, y) = overlay!(self; cursor);
+							let x      = self.bidi(y).map(|m| m.logical(0)).unwrap_or(0);
+
+							overlay!(self; cursor Position(Some(x), None));
 						}
 					}
 				}
@@ -944,7 +1090,7 @@ impl Overlay {
 					}
 
 					(command::Select::Normal, None) => {
-						let (x, y) = overlay!(self; cursor absolute);
+						let (x, y) = self.visual(overlay!(self; cursor absolute));
 
 						("VISUAL",
 							None,
@@ -972,7 +1118,7 @@ impl Overlay {
 					}
 
 					(command::Select::Block, None) => {
-						let (x, y) = overlay!(self; cursor absolute);
+						let (x, y) = self.visual(overlay!(self; cursor absolute));
 
 						("VISUAL BLOCK",
 							None,
@@ -1023,7 +1169,8 @@ impl Overlay {
 			Command::Copy(name) => {
 				if let Some(selection) = self.selector.current.take() {
 					actions.push(Action::Overlay(false));
-					actions.push(Action::Copy(name, self.selection(&selection)));
+					actions.push(Action::CopyHtml(name, self.selection(&selection),
+						capture::html(self.inner.config(), &self.cells(&selection))));
 				}
 			}
 
@@ -1126,13 +1273,107 @@ impl Overlay {
 					actions.push(Action::Copy(name, hint.into()));
 				}
 			}
+
+			// Cycle through the available encodings.
+			Command::Encoding => {
+				let encoding = self.inner.encoding().next();
+				self.inner.set_encoding(encoding);
+
+				overlay!(self; status mode encoding.name());
+			}
+
+			// Drop the scroll back, along with anything changed within it.
+			Command::Clear => {
+				if let Some(selection) = self.selector.current.take() {
+					self.highlight(Highlight::Selection(&selection), false);
+				}
+
+				let rows = self.inner.grid().view().len() as u32;
+				self.view.retain(|&(_, offset), _| offset < rows);
+
+				self.inner.clear_history();
+				self.scroll = 0;
+
+				overlay!(self; status mode "NORMAL");
+				self.touched.all();
+			}
+
+			// Toggle the session log.
+			Command::Log => {
+				let enabled = self.inner.toggle_logging();
+
+				if let Some(status) = self.status.as_mut() {
+					self.touched.line(self.inner.rows() - 1);
+					status.flags(if enabled { "LOG" } else { "" });
+				}
+			}
+
+			// Copy the whole scroll back and view as text and HTML.
+			Command::Capture(command::Capture::Copy(name)) => {
+				let config = self.inner.config();
+				let grid   = self.inner.grid();
+
+				actions.push(Action::Overlay(false));
+				actions.push(Action::CopyHtml(name, capture::all(config, grid, capture::Format::Text),
+					capture::all(config, grid, capture::Format::Html)));
+			}
+
+			// Save the whole scroll back and view to the configured file.
+			Command::Capture(command::Capture::Save) => {
+				let config = self.inner.config().overlay().capture();
+
+				if let Err(err) = capture::save(self.inner.config(), self.inner.grid(), config.path(), config.format()) {
+					error!(target: "cancer::overlay", "could not save capture: {}", err);
+				}
+			}
+
+			Command::Replay(control) => {
+				actions.push(Action::Replay(control));
+			}
 		}
 
+		// Scrolling may have brought more rows into view.
+		self.inflate_view();
+
 		actions
 	}
 
+	/// Inflate the scroll back rows between the given internal Y coordinates.
+	fn inflate_between(&mut self, top: u32, bottom: u32) {
+		let rows = self.inner.grid().view().len() as i64;
+		self.inner.inflate(rows - 1 - top as i64, rows - 1 - bottom as i64);
+	}
+
+	/// Inflate the scroll back rows in view.
+	fn inflate_view(&mut self) {
+		let (top, bottom) = (self.scroll + self.inner.grid().view().len() as u32 - 1, self.scroll);
+		self.inflate_between(top, bottom);
+	}
+
+	/// Inflate the scroll back rows within the selection.
+	fn inflate_selection(&mut self, selection: &Selection) {
+		let (top, bottom) = match *selection {
+			Selection::Normal { start, end } | Selection::Block { start, end } =>
+				(start.1, end.1),
+
+			Selection::Line { start, end } =>
+				(start, end),
+		};
+
+		self.inflate_between(top, bottom);
+	}
+
 	/// Turn the current selection to its text representation.
 	fn selection(&self, selection: &Selection) -> String {
+		self.cells(selection).iter()
+			.map(|line| line.iter().map(|c| c.value()).collect::<String>())
+			.collect::<Vec<_>>()
+			.join("\n")
+	}
+
+	/// Collect the cells of a row between the given display columns, in
+	/// logical order and without the trailing empty cells.
+	fn span(&self, y: u32, start: u32, end: u32) -> Vec<&Cell> {
 		/// Find the index of the first non-empty cell followed by only empty
 		/// cells.
 		fn edge(row: &Row, start: u32, end: u32) -> u32 {
@@ -1152,10 +1393,34 @@ impl Overlay {
 			found.unwrap_or(end)
 		}
 
+		let row = &self[y];
+
+		if let Some(map) = self.order(y) {
+			let mut cells = (0 .. self.inner.columns())
+				.filter(|&x| map.visual(x) >= start && map.visual(x) <= end)
+				.map(|x| &row[x as usize])
+				.collect::<Vec<_>>();
+
+			while cells.last().map(|c| c.is_empty()).unwrap_or(false) {
+				cells.pop();
+			}
+
+			cells
+		}
+		else {
+			(start ..= edge(row, start, end))
+				.map(|x| &row[x as usize])
+				.collect()
+		}
+	}
+
+	/// Collect the cells within the selection, one line for each row or for
+	/// each set of wrapped rows.
+	fn cells(&self, selection: &Selection) -> Vec<Vec<&Cell>> {
 		match *selection {
 			Selection::Normal { start, end } => {
 				let mut lines  = vec![];
-				let mut unwrap = None::<Vec<String>>;
+				let mut unwrap = None::<Vec<Vec<&Cell>>>;
 
 				// Iterate in reverse on the rows, so wrapped lines can be unwrapped.
 				for y in end.1 ..= start.1 {
@@ -1173,13 +1438,8 @@ impl Overlay {
 						(0, self.inner.columns() - 1)
 					};
 
-					let     row  = &self[y];
-					let mut line = String::new();
-
-					// Fill the current line.
-					for x in start ..= edge(row, start, end) {
-						line.push_str(row[x as usize].value());
-					}
+					let row  = &self[y];
+					let line = self.span(y, start, end);
 
 					// If the row is wrapped, push it up.
 					if row.is_wrapped() {
@@ -1203,45 +1463,23 @@ impl Overlay {
 					}
 				}
 
-				let mut result = String::new();
-
 				// Collect up the lines in reverse order, which happens to be the
-				// original order.
-				for lines in lines.into_iter().rev() {
-					// Collect up any wrapped lines, in reverse order, which again is the
-					// original order.
-					for line in lines.into_iter().rev() {
-						result.push_str(&line);
-					}
-
-					result.push('\n');
-				}
-
-				result.pop();
-				result
+				// original order, and any wrapped lines, in reverse order, which
+				// again is the original order.
+				lines.into_iter().rev()
+					.map(|lines| lines.into_iter().rev().flat_map(|l| l).collect())
+					.collect()
 			}
 
 			Selection::Block { start, end } => {
-				let mut result = String::new();
-
-				// Iterate in proper order.
-				for y in (end.1 ..= start.1).rev() {
-					let row = &self[y];
-
-					// Collect up from edge to edge.
-					for x in start.0 ..= edge(row, start.0, end.0) {
-						result.push_str(row[x as usize].value());
-					}
-
-					result.push('\n');
-				}
-
-				result.pop();
-				result
+				// Iterate in proper order, and collect up from edge to edge.
+				(end.1 ..= start.1).rev()
+					.map(|y| self.span(y, start.0, end.0))
+					.collect()
 			}
 
 			Selection::Line { start, end } => {
-				self.selection(&Selection::Normal {
+				self.cells(&Selection::Normal {
 					start: (0, start),
 					end:   (self.inner.columns() - 1, end)
 				})
@@ -1415,6 +1653,10 @@ impl Overlay {
 
 	/// Enable or disable highlighting of the given selection.
 	fn highlight(&mut self, what: Highlight, flag: bool) {
+		if let Highlight::Selection(selection) = what {
+			self.inflate_selection(selection);
+		}
+
 		match what {
 			Highlight::Selection(&Selection::Normal { start, end }) => {
 				// Adapt the horizontal edges based on the vertical position.
@@ -1432,7 +1674,12 @@ impl Overlay {
 						(0, self.inner.columns() - 1)
 					};
 
+					// The edges are in display order.
+					let map = self.order(y);
+
 					for x in start ..= end {
+						let x = map.as_ref().map(|m| m.logical(x)).unwrap_or(x);
+
 						if flag {
 							let mut cell = self[y][x as usize].clone();
 							cell.set_style(self.selector.style.clone());
@@ -1447,7 +1694,11 @@ impl Overlay {
 
 			Highlight::Selection(&Selection::Block { start, end }) => {
 				for y in end.1 ..= start.1 {
+					let map = self.order(y);
+
 					for x in start.0 ..= end.0 {
+						let x = map.as_ref().map(|m| m.logical(x)).unwrap_or(x);
+
 						if flag {
 							let mut cell = self[y][x as usize].clone();
 							cell.set_style(self.selector.style.clone());
@@ -1520,6 +1771,11 @@ impl Index<(u32, u32)> for Overlay {
 	type Output = Cell;
 
 	fn index(&self, (x, y): (u32, u32)) -> &Cell {
+		// The terminal status line is below everything else.
+		if y >= self.inner.rows() {
+			return &self.inner[(x, y)];
+		}
+
 		// If there's a status bar and the requested row is the last one, return
 		// the cell from the status.
 		if let Some(status) = self.status.as_ref() {
@@ -1548,7 +1804,7 @@ impl Index<(u32, u32)> for Overlay {
 
 		// Get the proper cell between the current view and the scroll back.
 		if offset as usize >= view.len() {
-			&back[back.len() - 1 - (offset as usize - view.len())][x as usize]
+			&back.get(back.len() - 1 - (offset as usize - view.len())).unwrap_or(&self.blank)[x as usize]
 		}
 		else {
 			&view[view.len() - 1 - offset as usize][x as usize]
@@ -1564,7 +1820,7 @@ impl Index<u32> for Overlay {
 		let view = self.inner.grid().view();
 
 		if y as usize >= view.len() {
-			&back[back.len() - 1 - (y as usize - view.len())]
+			back.get(back.len() - 1 - (y as usize - view.len())).unwrap_or(&self.blank)
 		}
 		else {
 			&view[view.len() - 1 - y as usize]
@@ -30,7 +30,9 @@ pub struct Status {
 
 	inner:    Vec<Cell>,
 	mode:     String,
+	flags:    String,
 	position: String,
+	right:    String,
 }
 
 impl Status {
@@ -44,7 +46,9 @@ impl Status {
 
 			inner:    vec![Cell::empty(style.clone()); cols as usize],
 			mode:     "".into(),
+			flags:    "".into(),
 			position: "".into(),
+			right:    "".into(),
 		}
 	}
 
@@ -63,11 +67,28 @@ impl Status {
 		self.mode = string;
 	}
 
+	/// Change the indicators shown before the cursor position.
+	pub fn flags<T: Into<String>>(&mut self, string: T) {
+		self.flags = string.into();
+		self.right();
+	}
+
 	/// Change the cursor position shown in the status bar.
 	pub fn position(&mut self, (x, y): (u32, u32)) {
-		let format = format!("{}:{}", y, x);
+		self.position = format!("{}:{}", y, x);
+		self.right();
+	}
+
+	/// Redraw the right side of the status bar.
+	fn right(&mut self) {
+		let format = if self.flags.is_empty() {
+			self.position.clone()
+		}
+		else {
+			format!("{} {}", self.flags, self.position)
+		};
 
-		for (_, cell) in self.position.graphemes(true).rev().zip(self.inner.iter_mut().rev()) {
+		for (_, cell) in self.right.graphemes(true).rev().zip(self.inner.iter_mut().rev()) {
 			cell.make_empty(self.style.clone());
 		}
 
@@ -75,7 +96,7 @@ impl Status {
 			cell.make_occupied(ch, self.style.clone());
 		}
 
-		self.position = format;
+		self.right = format;
 	}
 }
 
@@ -17,6 +17,7 @@
 
 use picto::Region;
 use platform::{Key, Mouse};
+use platform::socket::Control;
 
 #[allow(dead_code)]
 #[derive(Eq, PartialEq, Clone, Debug)]
@@ -47,4 +48,7 @@ pub enum Event {
 
 	/// Mouse event.
 	Mouse(Mouse),
+
+	/// Request from the control socket.
+	Control(Control),
 }
@@ -15,6 +15,9 @@
 // You should have received a copy of the GNU General Public License
 // along with cancer.  If not, see <http://www.gnu.org/licenses/>.
 
+mod source;
+pub use self::source::Source;
+
 mod tty;
 pub use self::tty::Tty;
 
@@ -24,14 +27,12 @@ pub use self::proxy::Proxy;
 pub mod event;
 pub use self::event::Event;
 
-pub mod key;
-pub use self::key::Key;
-
-pub mod mouse;
-pub use self::mouse::Mouse;
+pub mod socket;
+pub use self::socket::Socket;
 
-mod clipboard;
-pub use self::clipboard::Clipboard;
+pub use cancer::platform::{key, Key};
+pub use cancer::platform::{mouse, Mouse};
+pub use cancer::platform::Clipboard;
 
 #[cfg(all(unix, not(target_os = "macos")))]
 mod x11;
@@ -41,6 +41,11 @@ pub trait Proxy: Send {
 	/// Change the clipboard contents.
 	fn copy(&self, name: Clipboard, value: String) { }
 
+	/// Change the clipboard contents, offering an HTML version too.
+	fn copy_html(&self, name: Clipboard, value: String, html: String) {
+		self.copy(name, value)
+	}
+
 	/// Request the clipboard contents.
 	fn paste(&self, name: Clipboard) { }
 
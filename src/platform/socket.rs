@@ -0,0 +1,452 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A local control socket to script a running terminal.
+//!
+//! Every request is a line with a command and its arguments, every reply is
+//! a line with `ok`, optionally followed by a JSON string with the value, or
+//! `error` followed by a JSON string with the reason.
+//!
+//! ```text
+//! send-text "ls -l\n"
+//! get-text [screen | all | FROM TO] [text | ansi | html | rtf]
+//! get-cursor
+//! get-modes
+//! set-title "title"
+//! resize COLUMNS ROWS
+//! overlay enter | leave | press KEY...
+//! ```
+//!
+//! Lines for `get-text` are numbered like the rows in the view, negative ones
+//! are in the scroll back.
+
+use std::env;
+use std::fs;
+use std::mem;
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, FileTypeExt};
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{Sender, channel};
+use std::thread;
+use libc;
+
+use error;
+use cast::{encode, decode};
+use config::Config;
+use config::input::to_key;
+use terminal::capture::Format;
+use platform::Event;
+use platform::key::{self, Key};
+
+/// The most clients served at once, others are turned away.
+const CLIENTS: usize = 16;
+
+/// The socket, the file is removed when dropped.
+#[derive(Debug)]
+pub struct Socket {
+	path: PathBuf,
+}
+
+/// A request coming from the socket, along with where to reply.
+#[derive(Clone, Debug)]
+pub struct Control {
+	request: Request,
+	reply:   Sender<Reply>,
+}
+
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub enum Request {
+	/// Write the text to the program as if it was typed.
+	SendText(String),
+
+	/// Get the text of the given lines.
+	GetText(i64, i64, Format),
+
+	/// Get the cursor position.
+	GetCursor,
+
+	/// Get the active terminal modes.
+	GetModes,
+
+	/// Change the window title.
+	SetTitle(String),
+
+	/// Resize the window to the given columns and rows.
+	Resize(u32, u32),
+
+	/// Control the overlay.
+	Overlay(Overlay),
+}
+
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub enum Overlay {
+	/// Enter the overlay.
+	Enter,
+
+	/// Leave the overlay.
+	Leave,
+
+	/// Enter the overlay and press the given keys.
+	Press(Vec<Key>),
+}
+
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub enum Reply {
+	Ok,
+	Value(String),
+	Error(String),
+}
+
+impl Socket {
+	/// Get the path of the socket, the `CANCER_LISTEN` environment variable
+	/// takes precedence over the configuration.
+	///
+	/// In the path `{pid}` is replaced with the process ID and `{runtime}`
+	/// with the runtime directory, or a private one within the temporary
+	/// directory.
+	pub fn path(config: &Config) -> Option<PathBuf> {
+		let path = try!(option env::var("CANCER_LISTEN").ok()
+			.or_else(|| config.environment().socket().map(String::from)));
+
+		let runtime = if path.contains("{runtime}") {
+			match runtime() {
+				Ok(runtime) =>
+					runtime,
+
+				Err(err) => {
+					error!(target: "cancer::socket", "no private runtime directory: {}", err);
+					return None;
+				}
+			}
+		}
+		else {
+			PathBuf::new()
+		};
+
+		Some(path.replace("{pid}", &process::id().to_string())
+		         .replace("{runtime}", &runtime.to_string_lossy())
+		         .into())
+	}
+
+	/// Listen on the given path, sending requests to the manager.
+	pub fn bind<P: AsRef<Path>>(path: P, manager: Sender<Event>) -> error::Result<Self> {
+		let path = path.as_ref().to_path_buf();
+
+		// Remove the file if it's a socket of ours left over from a dead
+		// terminal, anything else is left alone and binding fails.
+		if let Ok(metadata) = fs::symlink_metadata(&path) {
+			if metadata.file_type().is_socket() && metadata.uid() == unsafe { libc::getuid() } &&
+			   UnixStream::connect(&path).is_err()
+			{
+				fs::remove_file(&path)?;
+			}
+		}
+
+		// The socket is created without permissions for anyone else, so there's
+		// no window where they could connect.
+		let listener = unsafe {
+			let mask     = libc::umask(0o177);
+			let listener = UnixListener::bind(&path);
+			libc::umask(mask);
+
+			listener?
+		};
+
+		thread::Builder::new().name("cancer::socket".into()).spawn(move || {
+			let clients = Arc::new(AtomicUsize::new(0));
+
+			for stream in listener.incoming() {
+				let stream = try!(continue stream);
+
+				// Only the user running the terminal can control it.
+				match peer(&stream) {
+					Ok(uid) if uid == unsafe { libc::getuid() } =>
+						(),
+
+					Ok(uid) => {
+						debug!(target: "cancer::socket", "rejected connection from user {}", uid);
+						continue;
+					}
+
+					Err(err) => {
+						error!(target: "cancer::socket", "could not get the peer credentials: {}", err);
+						continue;
+					}
+				}
+
+				if clients.fetch_add(1, Ordering::SeqCst) >= CLIENTS {
+					clients.fetch_sub(1, Ordering::SeqCst);
+					debug!(target: "cancer::socket", "rejected connection, too many clients");
+					continue;
+				}
+
+				let manager = manager.clone();
+				let clients = clients.clone();
+
+				thread::Builder::new().name("cancer::socket::client".into()).spawn(move || {
+					let _ = handle(stream, manager);
+					clients.fetch_sub(1, Ordering::SeqCst);
+				}).unwrap();
+			}
+		}).unwrap();
+
+		Ok(Socket {
+			path: path,
+		})
+	}
+}
+
+impl Drop for Socket {
+	fn drop(&mut self) {
+		let _ = fs::remove_file(&self.path);
+	}
+}
+
+impl Control {
+	/// Get the request.
+	pub fn request(&self) -> &Request {
+		&self.request
+	}
+
+	/// Reply to the request.
+	pub fn reply(&self, value: Reply) {
+		let _ = self.reply.send(value);
+	}
+}
+
+/// Ignore the reply channel, the request identifies the message.
+impl PartialEq for Control {
+	fn eq(&self, other: &Control) -> bool {
+		self.request == other.request
+	}
+}
+
+impl Eq for Control { }
+
+/// Get the runtime directory, without one a private directory is created
+/// within the temporary directory.
+fn runtime() -> io::Result<PathBuf> {
+	if let Some(path) = env::var_os("XDG_RUNTIME_DIR") {
+		return Ok(path.into());
+	}
+
+	let uid  = unsafe { libc::getuid() };
+	let path = env::temp_dir().join(format!("cancer-{}", uid));
+
+	if let Err(err) = fs::DirBuilder::new().mode(0o700).create(&path) {
+		if err.kind() != io::ErrorKind::AlreadyExists {
+			return Err(err);
+		}
+	}
+
+	// It may have been there already, so make sure it's ours and nobody else
+	// can get in.
+	let metadata = fs::symlink_metadata(&path)?;
+	if !metadata.is_dir() || metadata.uid() != uid || metadata.mode() & 0o077 != 0 {
+		return Err(io::Error::new(io::ErrorKind::PermissionDenied,
+			format!("{} is not a private directory", path.display())));
+	}
+
+	Ok(path)
+}
+
+/// Get the user ID of the process on the other side.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn peer(stream: &UnixStream) -> io::Result<libc::uid_t> {
+	let mut credentials = libc::ucred { pid: 0, uid: 0, gid: 0 };
+	let mut length      = mem::size_of::<libc::ucred>() as libc::socklen_t;
+
+	let result = unsafe {
+		libc::getsockopt(stream.as_raw_fd(), libc::SOL_SOCKET, libc::SO_PEERCRED,
+			&mut credentials as *mut _ as *mut libc::c_void, &mut length)
+	};
+
+	if result != 0 {
+		return Err(io::Error::last_os_error());
+	}
+
+	Ok(credentials.uid)
+}
+
+/// Get the user ID of the process on the other side.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn peer(stream: &UnixStream) -> io::Result<libc::uid_t> {
+	let mut uid = 0;
+	let mut gid = 0;
+
+	if unsafe { libc::getpeereid(stream.as_raw_fd(), &mut uid, &mut gid) } != 0 {
+		return Err(io::Error::last_os_error());
+	}
+
+	Ok(uid)
+}
+
+/// Serve the requests of a client until it goes away.
+fn handle(stream: UnixStream, manager: Sender<Event>) -> error::Result<()> {
+	let mut output = stream.try_clone()?;
+	let     input  = BufReader::new(stream);
+
+	for line in input.lines() {
+		let line = line?;
+
+		if line.trim().is_empty() {
+			continue;
+		}
+
+		let reply = match parse(&line) {
+			Ok(request) => {
+				let (sender, receiver) = channel();
+
+				try!(ok manager.send(Event::Control(Control {
+					request: request,
+					reply:   sender,
+				})));
+
+				try!(ok receiver.recv())
+			}
+
+			Err(reason) =>
+				Reply::Error(reason),
+		};
+
+		match reply {
+			Reply::Ok =>
+				writeln!(output, "ok")?,
+
+			Reply::Value(value) =>
+				writeln!(output, "ok {}", encode(&value))?,
+
+			Reply::Error(reason) =>
+				writeln!(output, "error {}", encode(&reason))?,
+		}
+	}
+
+	Ok(())
+}
+
+/// Parse a request line.
+fn parse(line: &str) -> Result<Request, String> {
+	let line            = line.trim();
+	let (command, rest) = line.split_at(line.find(' ').unwrap_or(line.len()));
+	let rest            = rest.trim();
+	let mut arguments   = rest.split_whitespace();
+
+	match command {
+		"send-text" =>
+			Ok(Request::SendText(string(rest)?)),
+
+		"get-text" => {
+			let mut format = Format::Text;
+			let mut lines  = (0, i64::max_value());
+			let mut range  = Vec::new();
+
+			for argument in arguments {
+				if let Ok(number) = argument.parse::<i64>() {
+					range.push(number);
+				}
+				else if argument == "all" {
+					lines = (i64::min_value(), i64::max_value());
+				}
+				else if argument == "screen" {
+					lines = (0, i64::max_value());
+				}
+				else if let Some(value) = Format::parse(argument) {
+					format = value;
+				}
+				else {
+					return Err(format!("unknown argument: {}", argument));
+				}
+			}
+
+			match range.len() {
+				0 => (),
+				2 => lines = (range[0], range[1]),
+				_ => return Err("the range needs a start and an end".into()),
+			}
+
+			Ok(Request::GetText(lines.0, lines.1, format))
+		}
+
+		"get-cursor" =>
+			Ok(Request::GetCursor),
+
+		"get-modes" =>
+			Ok(Request::GetModes),
+
+		"set-title" =>
+			Ok(Request::SetTitle(string(rest)?)),
+
+		"resize" => {
+			let columns = arguments.next().and_then(|v| v.parse().ok());
+			let rows    = arguments.next().and_then(|v| v.parse().ok());
+
+			match (columns, rows) {
+				(Some(columns), Some(rows)) if columns > 0 && rows > 0 =>
+					Ok(Request::Resize(columns, rows)),
+
+				_ =>
+					Err("resize needs the columns and rows".into()),
+			}
+		}
+
+		"overlay" => match arguments.next() {
+			Some("enter") =>
+				Ok(Request::Overlay(Overlay::Enter)),
+
+			Some("leave") =>
+				Ok(Request::Overlay(Overlay::Leave)),
+
+			Some("press") =>
+				Ok(Request::Overlay(Overlay::Press(arguments.map(press).collect()))),
+
+			_ =>
+				Err("overlay needs enter, leave or press".into()),
+		},
+
+		_ =>
+			Err(format!("unknown command: {}", command)),
+	}
+}
+
+/// Parse a string argument, either as a JSON string or as is.
+fn string(value: &str) -> Result<String, String> {
+	if value.starts_with('"') {
+		decode(value).map(|(value, _)| value).ok_or_else(|| "invalid string".into())
+	}
+	else {
+		Ok(value.into())
+	}
+}
+
+/// Parse a key for the overlay, single characters are pressed as they are
+/// typed, names follow the `input.prefix` syntax.
+fn press(name: &str) -> Key {
+	let mut chars = name.chars();
+
+	match (chars.next(), chars.next()) {
+		(Some(ch), None) =>
+			Key::new(ch.into(), if ch.is_uppercase() { key::SHIFT } else { key::Modifier::empty() }, Default::default()),
+
+		_ =>
+			to_key(name),
+	}
+}
@@ -0,0 +1,30 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::io::Write;
+use std::sync::mpsc::Receiver;
+
+use error;
+
+/// Where the terminal output comes from, and where its replies go.
+pub trait Source: Write + Send {
+	/// Take the receiving end of the output.
+	fn output(&mut self) -> Receiver<Vec<u8>>;
+
+	/// Resize the source.
+	fn resize(&mut self, width: u32, height: u32) -> error::Result<()>;
+}
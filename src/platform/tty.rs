@@ -22,10 +22,12 @@ use std::sync::mpsc::{Sender, Receiver, channel, sync_channel};
 
 use libc::{c_void, c_char, c_ushort, c_int, winsize};
 use libc::{SIGCHLD, SIGHUP, SIGINT, SIGQUIT, SIGTERM, SIGALRM, SIG_DFL, TIOCSCTTY, TIOCSWINSZ};
-use libc::{close, read, write, openpty, fork, setsid, dup2, signal, ioctl, getpwuid, getuid, execvp};
+use libc::{close, read, write, openpty, fork, setsid, dup2, signal, ioctl, getpwuid, getuid, execvp, kill};
 use libc::{fcntl, F_GETFL, F_SETFL, O_NONBLOCK};
+use libc::{waitpid, WNOHANG};
 
 use error::{self, Error};
+use platform::Source;
 
 #[derive(Debug)]
 pub struct Tty {
@@ -169,12 +171,34 @@ impl Tty {
 
 		(i_sender, o_receiver)
 	}
+}
+
+impl Drop for Tty {
+	fn drop(&mut self) {
+		// Hang up on the program, for when the tab is closed under it.
+		unsafe {
+			kill(self.id, SIGHUP);
+		}
+
+		// Reap it, waiting elsewhere if it doesn't go away right away.
+		if unsafe { waitpid(self.id, ptr::null_mut(), WNOHANG) } == 0 {
+			let id = self.id;
+
+			thread::Builder::new().name("cancer::tty::reaper".into()).spawn(move || {
+				unsafe {
+					waitpid(id, ptr::null_mut(), 0);
+				}
+			}).unwrap();
+		}
+	}
+}
 
-	pub fn output(&mut self) -> Receiver<Vec<u8>> {
+impl Source for Tty {
+	fn output(&mut self) -> Receiver<Vec<u8>> {
 		self.output.take().unwrap()
 	}
 
-	pub fn resize(&mut self, width: u32, height: u32) -> error::Result<()> {
+	fn resize(&mut self, width: u32, height: u32) -> error::Result<()> {
 		unsafe {
 			let size = winsize {
 				ws_row:    height as c_ushort,
@@ -67,7 +67,11 @@ impl platform::Proxy for Proxy {
 	}
 
 	fn copy(&self, name: Clipboard, value: String) {
-		self.request.send(Request::Copy(name, value)).unwrap();
+		self.request.send(Request::Copy(name, value, None)).unwrap();
+	}
+
+	fn copy_html(&self, name: Clipboard, value: String, html: String) {
+		self.request.send(Request::Copy(name, value, Some(html))).unwrap();
 	}
 
 	fn paste(&self, name: Clipboard) {
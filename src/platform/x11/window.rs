@@ -48,7 +48,7 @@ pub enum Request {
 	Urgent,
 	Title(String),
 	Resize(u32, u32),
-	Copy(Clipboard, String),
+	Copy(Clipboard, String, Option<String>),
 	Paste(Clipboard),
 }
 
@@ -165,6 +165,7 @@ impl Window {
 		let UTF8_STRING = xcb::intern_atom(&self.connection, false, "UTF8_STRING").get_reply().unwrap().atom();
 		let STRING      = xcb::ATOM_STRING;
 		let TARGETS     = xcb::intern_atom(&self.connection, false, "TARGETS").get_reply().unwrap().atom();
+		let HTML        = xcb::intern_atom(&self.connection, false, "text/html").get_reply().unwrap().atom();
 		let SELECTION   = xcb::intern_atom(&self.connection, false, "CANCER_CLIPBOARD").get_reply().unwrap().atom();
 
 		loop {
@@ -193,7 +194,7 @@ impl Window {
 								(xcb::CONFIG_WINDOW_HEIGHT as u16, h)]);
 						}
 
-						Request::Copy(name, value) => {
+						Request::Copy(name, value, html) => {
 							let atom = match name {
 								Clipboard::Primary   => PRIMARY,
 								Clipboard::Secondary => SECONDARY,
@@ -202,7 +203,7 @@ impl Window {
 
 							debug!(target: "cancer::platform::clipboard", "set clipboard: {:?}({:?}) = {:?}", name, atom, value);
 
-							clipboard.insert(atom, value);
+							clipboard.insert(atom, (value, html));
 							xcb::set_selection_owner(&self.connection, self.window, atom, xcb::CURRENT_TIME);
 							self.connection.flush();
 						}
@@ -273,16 +274,19 @@ impl Window {
 
 							match reply.name() {
 								"TARGETS" => {
+									let html    = clipboard.get(&event.selection()).map(|&(_, ref html)| html.is_some()).unwrap_or(false);
+									let targets = [TARGETS, STRING, UTF8_STRING, HTML];
+
 									xcb::change_property(&self.connection, xcb::PROP_MODE_REPLACE as u8,
-										event.requestor(), event.property(), xcb::ATOM_ATOM, 32, &[
-											TARGETS, STRING, UTF8_STRING]);
+										event.requestor(), event.property(), xcb::ATOM_ATOM, 32,
+										if html { &targets[..] } else { &targets[.. 3] });
 
 									xcb::send_event(&self.connection, false, event.requestor(), 0, &xcb::SelectionNotifyEvent::new(
 										event.time(), event.requestor(), event.selection(), event.target(), event.property()));
 								}
 
 								"UTF8_STRING" => {
-									if let Some(value) = clipboard.get(&event.selection()) {
+									if let Some(&(ref value, _)) = clipboard.get(&event.selection()) {
 										xcb::change_property(&self.connection, xcb::PROP_MODE_REPLACE as u8,
 											event.requestor(), event.property(), UTF8_STRING, 8, value.as_bytes());
 
@@ -292,7 +296,7 @@ impl Window {
 								}
 
 								"STRING" => {
-									if let Some(value) = clipboard.get(&event.selection()) {
+									if let Some(&(ref value, _)) = clipboard.get(&event.selection()) {
 										xcb::change_property(&self.connection, xcb::PROP_MODE_REPLACE as u8,
 											event.requestor(), event.property(), STRING, 8, value.as_bytes());
 
@@ -301,6 +305,16 @@ impl Window {
 									}
 								}
 
+								"text/html" => {
+									if let Some(&(_, Some(ref value))) = clipboard.get(&event.selection()) {
+										xcb::change_property(&self.connection, xcb::PROP_MODE_REPLACE as u8,
+											event.requestor(), event.property(), HTML, 8, value.as_bytes());
+
+										xcb::send_event(&self.connection, false, event.requestor(), 0, &xcb::SelectionNotifyEvent::new(
+											event.time(), event.requestor(), event.selection(), event.target(), event.property()));
+									}
+								}
+
 								_ => ()
 							}
 
@@ -340,7 +354,7 @@ impl Window {
 							try!(manager.send(Event::Mouse(Mouse::Click(mouse::Click {
 								press:    press,
 								button:   button,
-								modifier: key::Modifier::from(event.state()),
+								modifier: modifier(event.state()),
 								position: mouse::Position {
 									x: event.event_x() as u32,
 									y: event.event_y() as u32,
@@ -352,7 +366,7 @@ impl Window {
 							let event = unsafe { xcb::cast_event::<xcb::MotionNotifyEvent>(&event) };
 
 							try!(manager.send(Event::Mouse(Mouse::Motion(mouse::Motion {
-								modifier: key::Modifier::from(event.state()),
+								modifier: modifier(event.state()),
 								position: mouse::Position {
 									x: event.event_x() as u32,
 									y: event.event_y() as u32,
@@ -382,22 +396,21 @@ impl Window {
 	}
 }
 
-impl From<u16> for key::Modifier {
-	fn from(value: u16) -> Self {
-		let mut result = key::Modifier::empty();
+/// Convert the X11 modifier mask, `key::Modifier` lives in the library.
+fn modifier(value: u16) -> key::Modifier {
+	let mut result = key::Modifier::empty();
 
-		if (value as u32 & xcb::MOD_MASK_SHIFT) != 0 {
-			result.insert(key::SHIFT);
-		}
-
-		if (value as u32 & xcb::MOD_MASK_CONTROL) != 0 {
-			result.insert(key::CTRL);
-		}
+	if (value as u32 & xcb::MOD_MASK_SHIFT) != 0 {
+		result.insert(key::SHIFT);
+	}
 
-		if (value as u32 & xcb::MOD_MASK_4) != 0 {
-			result.insert(key::ALT);
-		}
+	if (value as u32 & xcb::MOD_MASK_CONTROL) != 0 {
+		result.insert(key::CTRL);
+	}
 
-		result
+	if (value as u32 & xcb::MOD_MASK_4) != 0 {
+		result.insert(key::ALT);
 	}
+
+	result
 }
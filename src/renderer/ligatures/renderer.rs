@@ -35,6 +35,7 @@
 use picto::Region;
 use sys::cairo;
 use interface::Interface;
+use tabs::Tabs;
 use renderer::State;
 use renderer::option::{self, Options};
 
@@ -66,4 +67,7 @@ impl Renderer {
 		self.context.rgba(&::picto::color::Rgba::new_u8(0, 0, 0, 255));
 		self.context.paint();
 	}
+
+	pub fn bar(&mut self, state: &State, tabs: &Tabs) {
+	}
 }
@@ -26,6 +26,7 @@ use renderer::{State, Margin};
 use renderer::{option, Options};
 use renderer::{standard, ligatures};
 use interface::Interface;
+use tabs::Tabs;
 
 pub struct Renderer {
 	state: State,
@@ -67,6 +68,7 @@ impl Renderer {
 			width:  width,
 			height: height,
 			margin: margin,
+			bar:    0,
 		};
 
 		let mode = if state.config().style().ligatures() {
@@ -82,6 +84,44 @@ impl Renderer {
 		}
 	}
 
+	/// Get the window dimensions for the given grid, including the tab bar.
+	pub fn size(&self, columns: u32, rows: u32) -> (u32, u32) {
+		let (width, height) = Renderer::dimensions(columns, rows, &self.state.config, &self.state.font);
+		(width, height + self.state.bar())
+	}
+
+	/// Show or hide the tab bar, returns whether the rows changed.
+	pub fn show_bar(&mut self, surface: &cairo::Surface, value: bool) -> bool {
+		if !self.state.show_bar(value) {
+			return false;
+		}
+
+		let (mode, state) = (&mut self.mode, &self.state);
+
+		match *mode {
+			Mode::Standard(ref mut renderer) =>
+				renderer.resize(surface, state),
+
+			Mode::Ligatures(ref mut renderer) =>
+				renderer.resize(surface, state),
+		}
+
+		true
+	}
+
+	/// Draw the tab bar.
+	pub fn tabs(&mut self, tabs: &Tabs) {
+		let (mode, state) = (&mut self.mode, &self.state);
+
+		match *mode {
+			Mode::Standard(ref mut renderer) =>
+				renderer.bar(state, tabs),
+
+			Mode::Ligatures(ref mut renderer) =>
+				renderer.bar(state, tabs),
+		}
+	}
+
 	/// Resize the renderer viewport.
 	pub fn resize(&mut self, surface: &cairo::Surface, width: u32, height: u32) {
 		let (mode, state) = (&mut self.mode, &mut self.state);
@@ -55,6 +55,7 @@ bitflags! {
 		const VALID    = 1 << 0;
 		const BLINKING = 1 << 1;
 		const REVERSE  = 1 << 2;
+		const PLACED   = 1 << 3;
 	}
 }
 
@@ -99,8 +100,9 @@ impl Cache {
 	/// Update the cache, returns `false` if the cache is valid.
 	///
 	/// The cell is seen as unchanged if it's valid, the style and content match
-	/// and the rendering options match.
-	pub fn update(&mut self, cell: &cell::Position, options: Options) -> bool {
+	/// and the rendering options match, cells covered by images are always seen
+	/// as changed.
+	pub fn update(&mut self, cell: &cell::Position, options: Options, placed: bool) -> bool {
 		debug_assert!(!cell.is_reference());
 
 		let index = (cell.y() * self.width + cell.x()) as usize;
@@ -110,6 +112,7 @@ impl Cache {
 			let cache = &self.inner[index];
 
 			if cache.flags.contains(VALID) &&
+			   !placed && !cache.flags.contains(PLACED) &&
 			   cache.flags.contains(REVERSE) == options.reverse() &&
 			   (!cache.style.attributes().contains(style::BLINK) ||
 				   cache.flags.contains(BLINKING) == options.blinking()) &&
@@ -128,6 +131,7 @@ impl Cache {
 			flags: VALID
 				| if options.blinking() { BLINKING } else { NONE }
 				| if options.reverse() { REVERSE } else { NONE }
+				| if placed { PLACED } else { NONE }
 		};
 
 		// Invalidate reference cells.
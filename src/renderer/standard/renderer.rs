@@ -17,13 +17,17 @@
 
 use std::mem;
 use std::rc::Rc;
+use std::collections::HashMap;
+use std::hash::BuildHasherDefault;
+use fnv::FnvHasher;
 
 use picto::Region;
 use config::style::Shape;
 use sys::cairo;
 use style;
-use terminal::{cell, cursor};
+use terminal::{cell, cursor, image, soft, bidi};
 use interface::Interface;
+use tabs::Tabs;
 use renderer::{State, Options};
 use renderer::standard::{Cache, Glyphs};
 
@@ -66,25 +70,92 @@ impl Renderer {
 			self.margin(state, &region);
 		}
 
+		// Rows are reordered for display only once per render.
+		let mut maps: HashMap<u32, _, BuildHasherDefault<FnvHasher>> = Default::default();
+
 		for cell in interface.iter(iter) {
-			self.cell(state, &cell, options);
+			let placed = interface.placed(cell.x(), cell.y());
+			let map    = maps.entry(cell.y()).or_insert_with(|| interface.bidi(cell.y()));
+			let cell   = visual(map, cell);
+
+			self.cell(state, &cell, options, &placed, interface.soft());
 		}
 
+		let cursor = interface.cursor();
+		let map    = maps.entry(cursor.cell().y()).or_insert_with(|| interface.bidi(cursor.cell().y()));
+		let placed = interface.placed(cursor.cell().x(), cursor.cell().y());
+		let cell   = visual(map, cursor.cell());
+
 		if options.cursor() {
-			self.cursor(state, &interface.cursor(), options);
+			self.cursor(state, &cursor::Cell::new(&cursor, cell), options, interface.soft());
 		}
 		else {
-			self.cell(state, &interface.cursor().cell(), options);
+			self.cell(state, &cell, options, &placed, interface.soft());
 		}
 
 		self.context.pop();
 		self.context.paint();
 	}
 
+	/// Draw the tab bar, the current tab has the terminal colors and the others
+	/// the status colors, activity is marked with `*` and bells with `!`.
+	pub fn bar(&mut self, state: &State, tabs: &Tabs) {
+		let (c, f, o) = (state.config(), state.font(), &mut self.context);
+
+		if state.bar() == 0 || tabs.len() == 0 {
+			return;
+		}
+
+		let (fg, bg) = (c.style().color().foreground(), c.style().color().background());
+		let status   = c.overlay().status();
+		let width    = state.width() / tabs.len() as u32;
+
+		o.push();
+		for (i, tab) in tabs.iter().enumerate() {
+			let x = i as u32 * width;
+			let w = if i + 1 == tabs.len() { state.width() - x } else { width };
+
+			let (foreground, background) = if tab.is_current() {
+				(fg, bg)
+			}
+			else if let Some(status) = status {
+				(status.foreground().unwrap_or(bg), status.background().unwrap_or(fg))
+			}
+			else {
+				(bg, fg)
+			};
+
+			let label = format!(" {}{} {}",
+				i + 1,
+				if tab.bell() { "!" } else if tab.activity() { "*" } else { "" },
+				tab.title());
+
+			let label = label.chars().take((w / f.width()) as usize).collect::<String>();
+			let attrs = if tab.is_current() { style::BOLD } else { style::NONE };
+
+			o.save();
+			{
+				o.rectangle(x as f64, 0.0, w as f64, state.bar() as f64);
+				o.clip();
+				o.rgba(background);
+				o.paint();
+
+				let computed = self.glyphs.compute(Rc::new(label), attrs);
+				o.move_to(x as f64, f.ascent() as f64);
+				o.rgba(foreground);
+				o.glyph(computed.text(), computed.glyphs());
+			}
+			o.restore();
+		}
+		o.pop();
+		o.paint();
+	}
+
 	/// Draw the margins within the given region.
 	pub fn margin(&mut self, state: &State, region: &Region) {
 		let (rows, columns)    = (state.rows(), state.columns());
 		let (c, f, o, s, h, v) = (state.config(), state.font(), &mut self.context, state.config().style().spacing(), state.margin().horizontal, state.margin().vertical);
+		let b                  = state.bar();
 
 		// Bail out if there's no margin.
 		if h == 0 && v == 0 {
@@ -108,15 +179,15 @@ impl Renderer {
 				o.fill();
 			}
 
-			// Top margin.
-			if region.y < v {
-				o.rectangle(region.x as f64, 0.0, region.width as f64, v as f64);
+			// Top margin, below the tab bar.
+			if region.y < b + v {
+				o.rectangle(region.x as f64, b as f64, region.width as f64, v as f64);
 				o.fill();
 			}
 
 			// Bottom margin.
 			if region.y + region.height >= state.height() - v {
-				o.rectangle(region.x as f64, (v + (rows * (f.height() + s))) as f64, region.width as f64, v as f64 * 2.0);
+				o.rectangle(region.x as f64, (b + v + (rows * (f.height() + s))) as f64, region.width as f64, v as f64 * 2.0);
 				o.fill();
 			}
 		}
@@ -124,7 +195,7 @@ impl Renderer {
 	}
 
 	/// Draw the cursor.
-	fn cursor(&mut self, state: &State, cursor: &cursor::Cell, options: Options) {
+	fn cursor(&mut self, state: &State, cursor: &cursor::Cell, options: Options, soft: &soft::Soft) {
 		self.cache.invalidate(&cursor.cell());
 
 		let (c, o, f) = (state.config(), &mut self.context, state.font());
@@ -136,7 +207,7 @@ impl Renderer {
 		let w = f.width() * cell.width();
 		let h = f.height() + c.style().spacing();
 		let x = state.margin().horizontal + (cell.x() * f.width());
-		let y = state.margin().vertical + (cell.y() * h);
+		let y = state.top() + (cell.y() * h);
 
 		o.save();
 		{
@@ -177,8 +248,13 @@ impl Renderer {
 					}
 				}
 
-				let computed = self.glyphs.compute(Rc::new(cell.value().into()), cell.style().attributes());
-				o.glyph(computed.text(), computed.glyphs());
+				if let Some(glyph) = soft.glyph(cell.value()) {
+					bitmap(o, glyph, (x, y), (w, f.height()));
+				}
+				else {
+					let computed = self.glyphs.compute(Rc::new(cell.value().into()), cell.style().attributes());
+					o.glyph(computed.text(), computed.glyphs());
+				}
 			}
 
 			// Render cursors that require to be on top.
@@ -221,9 +297,32 @@ impl Renderer {
 	}
 
 	/// Draw the given cell.
-	fn cell(&mut self, state: &State, cell: &cell::Position, options: Options) -> bool {
+	fn cell(&mut self, state: &State, cell: &cell::Position, options: Options, placed: &[image::Placed], soft: &soft::Soft) -> bool {
+		// Draw the parts of the placed images that are either below or above the
+		// text.
+		fn images(o: &mut cairo::Context, state: &State, placed: &[image::Placed], below: bool) {
+			let (c, f) = (state.config(), state.font());
+			let h      = f.height() + c.style().spacing();
+
+			for p in placed.iter().filter(|p| (p.placement.z < 0) == below) {
+				let x = state.margin().horizontal as i64 + p.origin.0 * f.width() as i64 + p.placement.offset.0 as i64;
+				let y = state.top() as i64 + p.origin.1 * h as i64 + p.placement.offset.1 as i64;
+
+				let (w, h)           = p.placement.size;
+				let (sx, sy, sw, sh) = p.placement.source;
+
+				if w == 0 || h == 0 || sw == 0 || sh == 0 {
+					continue;
+				}
+
+				o.image_region(&p.image,
+					(x as f64, y as f64, w as f64, h as f64),
+					(sx as f64, sy as f64, sw as f64, sh as f64));
+			}
+		}
+
 		// Bail out if the cell is up to date.
-		if !self.cache.update(cell, options) && !options.damage() {
+		if !self.cache.update(cell, options, !placed.is_empty()) && !options.damage() {
 			return false;
 		}
 
@@ -246,7 +345,7 @@ impl Renderer {
 		let w = f.width() * cell.width();
 		let h = f.height() + c.style().spacing();
 		let x = state.margin().horizontal + (cell.x() * f.width());
-		let y = state.margin().vertical + (cell.y() * h);
+		let y = state.top() + (cell.y() * h);
 
 		o.save();
 		{
@@ -256,14 +355,22 @@ impl Renderer {
 			o.rgba(bg);
 			o.paint();
 
+			// Draw the images below the text.
+			images(o, state, placed, true);
+
 			// Draw the glyph.
 			if !cell.style().attributes().contains(style::BLINK) || !options.blinking() {
 				if cell.is_occupied() {
 					o.move_to(x as f64, (y + f.ascent()) as f64);
 					o.rgba(fg);
 
-					let computed = self.glyphs.compute(Rc::new(cell.value().into()), cell.style().attributes());
-					o.glyph(computed.text(), computed.glyphs());
+					if let Some(glyph) = soft.glyph(cell.value()) {
+						bitmap(o, glyph, (x, y), (w, f.height()));
+					}
+					else {
+						let computed = self.glyphs.compute(Rc::new(cell.value().into()), cell.style().attributes());
+						o.glyph(computed.text(), computed.glyphs());
+					}
 				}
 				else if cell.is_image() {
 					o.image(cell.image(), x as f64, y as f64);
@@ -289,9 +396,39 @@ impl Renderer {
 					o.fill();
 				}
 			}
+
+			// Draw the images above the text.
+			images(o, state, placed, false);
 		}
 		o.restore();
 
 		true
 	}
 }
+
+/// Move the cell to its display position within a reordered row.
+fn visual<'a>(map: &Option<bidi::Map>, cell: cell::Position<'a>) -> cell::Position<'a> {
+	if let Some(ref map) = *map {
+		let x = map.visual(cell.x());
+		cell.at(x)
+	}
+	else {
+		cell
+	}
+}
+
+/// Draw a soft glyph scaled to the cell size, with the current source.
+fn bitmap(o: &mut cairo::Context, glyph: &soft::Glyph, (x, y): (u32, u32), (width, height): (u32, u32)) {
+	let sx = width as f64 / glyph.width() as f64;
+	let sy = height as f64 / glyph.height() as f64;
+
+	for gy in 0 .. glyph.height() {
+		for gx in 0 .. glyph.width() {
+			if glyph.get(gx, gy) {
+				o.rectangle(x as f64 + gx as f64 * sx, y as f64 + gy as f64 * sy, sx, sy);
+			}
+		}
+	}
+
+	o.fill();
+}
@@ -30,6 +30,7 @@ pub struct State {
 	pub(super) width:  u32,
 	pub(super) height: u32,
 	pub(super) margin: Margin,
+	pub(super) bar:    u32,
 }
 
 /// Adaptable margins depending on the view size.
@@ -60,9 +61,19 @@ impl State {
 		self.height
 	}
 
+	/// The height of the tab bar, zero when hidden.
+	pub fn bar(&self) -> u32 {
+		self.bar
+	}
+
+	/// Where the first row starts.
+	pub fn top(&self) -> u32 {
+		self.bar + self.margin.vertical
+	}
+
 	/// How many rows fit the view.
 	pub fn rows(&self) -> u32 {
-		(self.height - (self.margin.vertical * 2)) /
+		(self.height - self.bar - (self.margin.vertical * 2)) /
 			(self.font.height() + self.config.style().spacing())
 	}
 
@@ -85,20 +96,42 @@ impl State {
 			((width - (m * 2)) % self.font.width()) / 2;
 
 		self.margin.vertical = m +
-			((height - (m * 2)) % (self.font.height() + s)) / 2;
+			((height - self.bar - (m * 2)) % (self.font.height() + s)) / 2;
 
 		self.width  = width;
 		self.height = height;
 	}
 
+	/// Show or hide the tab bar, returns whether it changed.
+	pub fn show_bar(&mut self, value: bool) -> bool {
+		let bar = if value { self.font.height() + self.config.style().spacing() } else { 0 };
+
+		if bar == self.bar {
+			return false;
+		}
+
+		let (width, height) = (self.width, self.height);
+		self.bar = bar;
+		self.resize(width, height);
+
+		true
+	}
+
 	/// Find the cell position from the real position.
 	pub fn position(&self, x: u32, y: u32) -> Option<(u32, u32)> {
 		let (f, h, v, s) = (&self.font, self.margin.horizontal, self.margin.vertical, self.config.style().spacing());
 
+		// Bail out if it's on the tab bar, then make it relative to the view.
+		if y < self.bar {
+			return None;
+		}
+
+		let y = y - self.bar;
+
 		// Check if the region falls exactly within a margin, if so bail out.
 		if h != 0 && v != 0 &&
 		   (x < h || x >= self.width - h ||
-		    y < v || y >= self.height - v)
+		    y < v || y >= self.height - self.bar - v)
 		{
 			return None;
 		}
@@ -121,12 +154,21 @@ impl State {
 	pub fn damaged(&self, region: &Region) -> Region {
 		let (f, h, v, s) = (&self.font, self.margin.horizontal, self.margin.vertical, self.config.style().spacing());
 
+		// Bail out if it's all on the tab bar, then make it relative to the view.
+		if region.y + region.height <= self.bar {
+			return Region::from(0, 0, 0, 0);
+		}
+
+		let view   = self.height - self.bar;
+		let region = Region::from(region.x, region.y.saturating_sub(self.bar), region.width,
+			region.height - self.bar.saturating_sub(region.y));
+
 		// Check if the region falls exactly within a margin, if so bail out.
 		if h != 0 && v != 0 &&
 		   ((region.x < h && region.width <= h - region.x) ||
 		    (region.x >= self.width - h) ||
 		    (region.y < v && region.height <= v - region.y) ||
-		    (region.y >= self.height - v))
+		    (region.y >= view - v))
 		{
 			return Region::from(0, 0, 0, 0);
 		}
@@ -147,7 +189,7 @@ impl State {
 		// Remove margins from height.
 		let h = region.height
 			.saturating_sub(v.saturating_sub(region.y))
-			.saturating_sub(v.saturating_sub(self.height - (region.y + region.height))) as f32;
+			.saturating_sub(v.saturating_sub(view - (region.y + region.height))) as f32;
 
 		let x = (x / width).floor() as u32;
 		let y = (y / height).floor() as u32;
@@ -16,6 +16,7 @@
 // along with cancer.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::mem;
+use std::ffi::CString;
 
 use libc::c_int;
 use ffi::cairo::*;
@@ -93,6 +94,39 @@ impl Context {
 		}
 	}
 
+	pub fn curve_to(&mut self, x1: f64, y1: f64, x2: f64, y2: f64, x3: f64, y3: f64) {
+		unsafe {
+			cairo_curve_to(self.0, x1, y1, x2, y2, x3, y3);
+		}
+	}
+
+	pub fn arc(&mut self, x: f64, y: f64, radius: f64, start: f64, end: f64) {
+		unsafe {
+			if start <= end {
+				cairo_arc(self.0, x, y, radius, start, end);
+			}
+			else {
+				cairo_arc_negative(self.0, x, y, radius, start, end);
+			}
+		}
+	}
+
+	pub fn new_path(&mut self) {
+		unsafe {
+			cairo_new_path(self.0);
+		}
+	}
+
+	pub fn current_point(&mut self) -> (f64, f64) {
+		unsafe {
+			let mut x = 0.0;
+			let mut y = 0.0;
+			cairo_get_current_point(self.0, &mut x, &mut y);
+
+			(x, y)
+		}
+	}
+
 	pub fn line_width(&mut self, w: f64) {
 		unsafe {
 			cairo_set_line_width(self.0, w);
@@ -125,6 +159,16 @@ impl Context {
 		}
 	}
 
+	/// Draw text with the builtin font, moving the current point after it.
+	pub fn text<T: AsRef<str>>(&mut self, size: f64, text: T) {
+		if let Ok(text) = CString::new(text.as_ref()) {
+			unsafe {
+				cairo_set_font_size(self.0, size);
+				cairo_show_text(self.0, text.as_ptr());
+			}
+		}
+	}
+
 	pub fn image(&mut self, image: &Image, x: f64, y: f64) {
 		unsafe {
 			let mut matrix  = mem::uninitialized();
@@ -138,6 +182,24 @@ impl Context {
 		}
 	}
 
+	pub fn image_region(&mut self, image: &Image, (x, y, w, h): (f64, f64, f64, f64), (sx, sy, sw, sh): (f64, f64, f64, f64)) {
+		unsafe {
+			cairo_save(self.0);
+			cairo_rectangle(self.0, x, y, w, h);
+			cairo_clip(self.0);
+
+			let mut matrix  = mem::uninitialized();
+			cairo_matrix_init(&mut matrix, sw / w, 0.0, 0.0, sh / h, sx - x * (sw / w), sy - y * (sh / h));
+
+			let pattern = image.pattern();
+			cairo_pattern_set_matrix(pattern, &matrix);
+
+			cairo_set_source(self.0, pattern);
+			cairo_paint(self.0);
+			cairo_restore(self.0);
+		}
+	}
+
 	pub fn operator(&mut self, operator: Operator) {
 		unsafe {
 			cairo_set_operator(self.0, operator);
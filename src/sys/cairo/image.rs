@@ -48,6 +48,25 @@ impl Image {
 		}
 	}
 
+	/// Create an image from non-premultiplied RGBA pixels.
+	pub fn from_rgba(width: u32, height: u32, data: &[u8]) -> Self {
+		let mut image = Image::new(width, height);
+
+		for (i, px) in data.chunks(4).take((width * height) as usize).enumerate() {
+			let x = i as u32 % width;
+			let y = i as u32 / width;
+			let a = px[3] as u32;
+
+			image.set(x, y, &(
+				(px[0] as u32 * a / 255) as u8,
+				(px[1] as u32 * a / 255) as u8,
+				(px[2] as u32 * a / 255) as u8,
+				a as u8));
+		}
+
+		image
+	}
+
 	pub fn width(&self) -> u32 {
 		self.width
 	}
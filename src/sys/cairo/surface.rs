@@ -15,8 +15,13 @@
 // You should have received a copy of the GNU General Public License
 // along with cancer.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::slice;
+
+use libc::c_int;
 use ffi::cairo::*;
+#[cfg(feature = "window")]
 use ffi::cairo::platform::*;
+use super::Image;
 
 #[derive(Debug)]
 pub struct Surface(pub *mut cairo_surface_t);
@@ -24,14 +29,42 @@ pub struct Surface(pub *mut cairo_surface_t);
 unsafe impl Send for Surface { }
 
 impl Surface {
+	/// Create an in-memory ARGB surface, used for offscreen drawing.
+	pub fn image(width: u32, height: u32) -> Self {
+		unsafe {
+			Surface(cairo_image_surface_create(cairo_format_t::Argb32, width as c_int, height as c_int))
+		}
+	}
+
 	pub fn flush(&self) {
 		unsafe {
 			cairo_surface_flush(self.0);
 		}
 	}
+
+	/// Copy a region of an in-memory surface into an image.
+	pub fn to_image(&self, (x, y, width, height): (u32, u32, u32, u32)) -> Image {
+		unsafe {
+			self.flush();
+
+			let stride = cairo_image_surface_get_stride(self.0) as usize;
+			let data   = cairo_image_surface_get_data(self.0);
+			let mut image = Image::new(width, height);
+
+			for row in 0 .. height {
+				let source = slice::from_raw_parts(data.offset(((y + row) as usize * stride + x as usize * 4) as isize), width as usize * 4);
+
+				for (column, px) in source.chunks(4).enumerate() {
+					image.set(column as u32, row, &(px[2], px[1], px[0], px[3]));
+				}
+			}
+
+			image
+		}
+	}
 }
 
-#[cfg(all(unix, not(target_os = "macos")))]
+#[cfg(all(unix, not(target_os = "macos"), feature = "window"))]
 impl Surface {
 	pub fn new(connection: &xcb::Connection, drawable: xcb::Drawable, visual: xcb::Visualtype, width: u32, height: u32) -> Self {
 		unsafe {
@@ -40,7 +73,7 @@ impl Surface {
 	}
 }
 
-#[cfg(target_os = "macos")]
+#[cfg(all(target_os = "macos", feature = "window"))]
 impl Surface {
 	pub fn new(context: *mut c_void, width: u32, height: u32) -> Self {
 		unsafe {
@@ -0,0 +1,271 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::io::Write;
+use std::mem;
+use std::slice;
+use std::thread;
+use std::sync::mpsc::{Sender, Receiver};
+
+use error;
+use interface::{Interface, Action};
+use platform::Source;
+
+/// A terminal within a window.
+pub struct Tab {
+	id:       u32,
+	title:    String,
+	activity: bool,
+	bell:     bool,
+
+	// The current tab is driven by the runner, the others are parked here.
+	parked: Option<(Interface, Box<Source>)>,
+}
+
+/// The tabs within a window, in display order.
+pub struct Tabs {
+	list:    Vec<Tab>,
+	current: usize,
+	next:    u32,
+	title:   String,
+}
+
+impl Tab {
+	fn new(id: u32, title: String) -> Self {
+		Tab {
+			id:       id,
+			title:    title,
+			activity: false,
+			bell:     false,
+
+			parked: None,
+		}
+	}
+
+	/// Get the identifier the output is tagged with.
+	pub fn id(&self) -> u32 {
+		self.id
+	}
+
+	/// Get the title set by the program.
+	pub fn title(&self) -> &str {
+		&self.title
+	}
+
+	/// Check if there has been output since the tab was last seen.
+	pub fn activity(&self) -> bool {
+		self.activity
+	}
+
+	/// Check if the bell rang since the tab was last seen.
+	pub fn bell(&self) -> bool {
+		self.bell
+	}
+
+	/// Check if the tab is the current one.
+	pub fn is_current(&self) -> bool {
+		self.parked.is_none()
+	}
+}
+
+impl Tabs {
+	/// Create the tabs with the initial one, the title is used until the
+	/// program sets one.
+	pub fn new(title: String) -> Self {
+		Tabs {
+			list:    vec![Tab::new(0, title.clone())],
+			current: 0,
+			next:    1,
+			title:   title,
+		}
+	}
+
+	/// Get the number of tabs.
+	pub fn len(&self) -> usize {
+		self.list.len()
+	}
+
+	/// Iterate over the tabs in display order.
+	pub fn iter(&self) -> slice::Iter<Tab> {
+		self.list.iter()
+	}
+
+	/// Get the current tab.
+	pub fn current(&self) -> &Tab {
+		&self.list[self.current]
+	}
+
+	/// Change the title of the current tab.
+	pub fn set_title(&mut self, title: String) {
+		self.list[self.current].title = title;
+	}
+
+	/// Add a tab after the current one and switch to it, returning its
+	/// identifier.
+	pub fn create(&mut self, interface: &mut Interface, source: &mut Box<Source>, (new, output): (Interface, Box<Source>)) -> u32 {
+		let id = self.next;
+		self.next += 1;
+
+		self.list[self.current].parked = Some((mem::replace(interface, new), mem::replace(source, output)));
+		self.current += 1;
+		self.list.insert(self.current, Tab::new(id, self.title.clone()));
+
+		id
+	}
+
+	/// Close the tab with the given identifier, switching away from it if
+	/// it's the current one; returns `false` if it's the last one.
+	pub fn close(&mut self, id: u32, interface: &mut Interface, source: &mut Box<Source>) -> bool {
+		let index = if let Some(index) = self.list.iter().position(|t| t.id == id) {
+			index
+		}
+		else {
+			return true;
+		};
+
+		if self.list.len() == 1 {
+			return false;
+		}
+
+		if index == self.current {
+			let next = if index + 1 < self.list.len() { index + 1 } else { index - 1 };
+			self.switch(next, interface, source);
+		}
+
+		self.list.remove(index);
+
+		if index < self.current {
+			self.current -= 1;
+		}
+
+		true
+	}
+
+	/// Switch to the tab at the given position.
+	pub fn switch(&mut self, index: usize, interface: &mut Interface, source: &mut Box<Source>) {
+		if index == self.current || index >= self.list.len() {
+			return;
+		}
+
+		let (new, output) = self.list[index].parked.take().unwrap();
+		self.list[self.current].parked = Some((mem::replace(interface, new), mem::replace(source, output)));
+		self.current = index;
+
+		let tab = &mut self.list[index];
+		tab.activity = false;
+		tab.bell     = false;
+	}
+
+	/// Switch to the next tab, wrapping around.
+	pub fn next(&mut self, interface: &mut Interface, source: &mut Box<Source>) {
+		let index = (self.current + 1) % self.list.len();
+		self.switch(index, interface, source);
+	}
+
+	/// Switch to the previous tab, wrapping around.
+	pub fn previous(&mut self, interface: &mut Interface, source: &mut Box<Source>) {
+		let index = (self.current + self.list.len() - 1) % self.list.len();
+		self.switch(index, interface, source);
+	}
+
+	/// Move the current tab by the given number of places.
+	pub fn shift(&mut self, by: i32) {
+		let index = (self.current as i32 + by).max(0).min(self.list.len() as i32 - 1) as usize;
+		let tab   = self.list.remove(self.current);
+
+		self.list.insert(index, tab);
+		self.current = index;
+	}
+
+	/// Feed output to a parked tab, returning whether the tab bar changed and
+	/// the actions that concern the window.
+	pub fn input(&mut self, id: u32, input: &[u8]) -> error::Result<(bool, Vec<Action>)> {
+		let tab = if let Some(tab) = self.list.iter_mut().find(|t| t.id == id) {
+			tab
+		}
+		else {
+			return Ok((false, Vec::new()));
+		};
+
+		let (ref mut interface, ref mut source) = *if let Some(parked) = tab.parked.as_mut() {
+			parked
+		}
+		else {
+			return Ok((false, Vec::new()));
+		};
+
+		let mut changed = !tab.activity;
+		let mut rest    = Vec::new();
+		tab.activity    = true;
+
+		for action in interface.input(input, source.by_ref())?.0 {
+			match action {
+				Action::Title(title) => {
+					tab.title = title;
+					changed   = true;
+				}
+
+				Action::Urgent => {
+					changed  = changed || !tab.bell;
+					tab.bell = true;
+				}
+
+				Action::Tty(columns, rows) => {
+					source.resize(columns, rows)?;
+				}
+
+				action =>
+					rest.push(action),
+			}
+		}
+
+		source.flush()?;
+		Ok((changed, rest))
+	}
+
+	/// Resize the parked tabs, leaving their overlays.
+	pub fn resize(&mut self, columns: u32, rows: u32) -> error::Result<()> {
+		for tab in &mut self.list {
+			if let Some((mut interface, mut source)) = tab.parked.take() {
+				if interface.overlay() {
+					interface = interface.into_inner(source.by_ref())?.into();
+				}
+
+				if interface.columns() != columns || interface.region().height != rows {
+					interface.resize(columns, rows);
+					source.resize(interface.columns(), interface.rows())?;
+				}
+
+				tab.parked = Some((interface, source));
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/// Forward the output of a tab to the runner, tagged with its identifier,
+/// `None` is sent when the output ends.
+pub fn forward(id: u32, output: Receiver<Vec<u8>>, runner: Sender<(u32, Option<Vec<u8>>)>) {
+	thread::Builder::new().name("cancer::tab".into()).spawn(move || {
+		for input in output {
+			try!(return runner.send((id, Some(input))));
+		}
+
+		let _ = runner.send((id, None));
+	}).unwrap();
+}
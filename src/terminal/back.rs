@@ -0,0 +1,815 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+use std::borrow::Cow;
+use std::collections::{HashMap, VecDeque};
+use std::hash::BuildHasherDefault;
+use std::rc::Rc;
+use std::mem;
+use std::str;
+use fnv::FnvHasher;
+use picto::color::Rgba;
+
+use style::{Style, Attributes};
+use terminal::{Cell, Row};
+use terminal::grid::History;
+use terminal::spill::Spill;
+
+/// The scroll back, rows are kept in a compact form and inflated on demand.
+#[derive(Debug)]
+pub struct Back {
+	rows:   VecDeque<Stored>,
+	styles: Styles,
+	size:   usize,
+
+	spill:    Option<Spill>,
+	columns:  u32,
+	inflated: HashMap<usize, Row, BuildHasherDefault<FnvHasher>>,
+}
+
+#[derive(Debug)]
+enum Stored {
+	Row(Row),
+	Compact(Compact),
+}
+
+/// A row as a UTF-8 buffer and style spans.
+#[derive(Debug)]
+struct Compact {
+	text:    Box<str>,
+	spans:   Box<[Span]>,
+	lengths: Option<Box<[u8]>>,
+	wrapped: bool,
+}
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+struct Span {
+	kind:  Kind,
+	count: u16,
+	style: u32,
+}
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+enum Kind {
+	Empty,
+	Occupied,
+	Reference,
+}
+
+/// Table of interned styles, counting the cells using them so unused ones
+/// can be dropped.
+#[derive(Default, Debug)]
+struct Styles {
+	inner:   Vec<Option<(Rc<Style>, usize)>>,
+	indices: HashMap<Key, u32, BuildHasherDefault<FnvHasher>>,
+	free:    Vec<u32>,
+}
+
+/// A hashable form of a style, colors are compared by their bits.
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+struct Key(Option<[u64; 4]>, Option<[u64; 4]>, u8);
+
+impl Back {
+	/// Create a new scroll back, optionally spilling evicted rows to disk up to
+	/// the given number of bytes.
+	pub fn new(spill: Option<usize>) -> Self {
+		let spill = if let Some(limit) = spill {
+			match Spill::new(limit) {
+				Ok(spill) =>
+					Some(spill),
+
+				Err(err) => {
+					error!(target: "cancer::terminal::back", "could not create spill file: {}", err);
+					None
+				}
+			}
+		}
+		else {
+			None
+		};
+
+		Back {
+			rows:     VecDeque::new(),
+			styles:   Styles::default(),
+			size:     0,
+			spill:    spill,
+			columns:  0,
+			inflated: Default::default(),
+		}
+	}
+
+	/// Get the number of rows, including the spilled ones.
+	pub fn len(&self) -> usize {
+		self.spilled() + self.rows.len()
+	}
+
+	/// Check if there are no rows.
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Get the number of rows spilled to disk, they come before the ones in
+	/// memory.
+	pub fn spilled(&self) -> usize {
+		self.spill.as_ref().map(|s| s.len()).unwrap_or(0)
+	}
+
+	/// Get the approximate memory used by the rows and their styles.
+	pub fn size(&self) -> usize {
+		self.size + self.styles.size()
+	}
+
+	/// Reflow the rows in memory to the given width.
+	///
+	/// It goes a line at a time, so only the rows of one line are inflated at
+	/// once, and lines made of a single row that fits are left as they are to
+	/// be padded when read. Spilled rows are never reflowed, they're only
+	/// padded or cut to the width when read.
+	pub fn reflow<F: FnMut(&mut VecDeque<Row>)>(&mut self, columns: u32, mut reflow: F) {
+		self.deflate();
+
+		if self.columns == columns {
+			return;
+		}
+
+		self.columns = columns;
+
+		let mut rows = mem::replace(&mut self.rows, VecDeque::new()).into_iter().peekable();
+		let mut line = VecDeque::new();
+
+		while let Some(first) = rows.next() {
+			let mut group = vec![first];
+			while rows.peek().map(Stored::is_wrapped).unwrap_or(false) {
+				group.push(rows.next().unwrap());
+			}
+
+			let fits = group.len() == 1 && match group[0] {
+				Stored::Compact(ref compact) =>
+					!compact.wrapped && compact.width() <= columns as usize,
+
+				Stored::Row(..) =>
+					false,
+			};
+
+			if fits {
+				self.rows.extend(group);
+				continue;
+			}
+
+			// The start of the line may have been dropped already.
+			let orphan = group[0].is_wrapped();
+
+			for stored in group {
+				self.size -= stored.size();
+				line.push_back(stored.into_row(&mut self.styles));
+			}
+
+			line[0].wrapped = false;
+			reflow(&mut line);
+
+			if let Some(row) = line.front_mut() {
+				row.wrapped = orphan;
+			}
+
+			for row in line.drain(..) {
+				self.store(row);
+			}
+		}
+	}
+
+	/// Push a row at the end, returns the row if it can be reused.
+	pub fn push(&mut self, row: Row) -> Option<Row> {
+		self.deflate();
+		self.store(row)
+	}
+
+	/// Store a row at the end, returns the row if it can be reused.
+	fn store(&mut self, row: Row) -> Option<Row> {
+		let (stored, reuse) = if let Some(compact) = Compact::new(&row, &mut self.styles, false) {
+			(Stored::Compact(compact), Some(row))
+		}
+		else {
+			(Stored::Row(row), None)
+		};
+
+		self.size += stored.size();
+		self.rows.push_back(stored);
+
+		reuse
+	}
+
+	/// Pop the last row.
+	pub fn pop(&mut self) -> Option<Row> {
+		self.deflate();
+
+		if let Some(stored) = self.rows.pop_back() {
+			self.size -= stored.size();
+			return Some(fit(stored.into_row(&mut self.styles), self.columns));
+		}
+
+		let data = match self.spill.as_mut().map(|s| s.pop()) {
+			Some(Ok(Some(data))) =>
+				data,
+
+			Some(Err(err)) => {
+				error!(target: "cancer::terminal::back", "could not read spill file: {}", err);
+				return None;
+			}
+
+			_ =>
+				return None,
+		};
+
+		Compact::decode(&data).map(|row| fit(row, self.columns))
+	}
+
+	/// Keep the rows in memory within the limit, evicted rows are spilled to
+	/// disk if enabled.
+	pub fn limit(&mut self, history: History) {
+		let overflow = match history {
+			History::Rows(limit) =>
+				self.rows.len().saturating_sub(limit),
+
+			History::Bytes(limit) => {
+				let mut size     = self.size();
+				let mut overflow = 0;
+
+				while size > limit && overflow < self.rows.len() {
+					size     -= self.rows[overflow].size();
+					overflow += 1;
+				}
+
+				overflow
+			}
+
+			History::Unlimited =>
+				0,
+		};
+
+		if overflow == 0 {
+			return;
+		}
+
+		self.deflate();
+
+		let mut buffer = Vec::new();
+		for stored in self.rows.drain(.. overflow) {
+			self.size -= stored.size();
+
+			// Spilled rows carry their styles, so they're released either way.
+			if let Some(spill) = self.spill.as_mut() {
+				let compact = match stored {
+					Stored::Compact(compact) =>
+						compact,
+
+					Stored::Row(row) =>
+						Compact::new(&row, &mut self.styles, true).unwrap(),
+				};
+
+				buffer.clear();
+				compact.encode(&self.styles, &mut buffer);
+				self.styles.release(&compact);
+
+				if let Err(err) = spill.push(&buffer) {
+					error!(target: "cancer::terminal::back", "could not write spill file: {}", err);
+				}
+			}
+			else if let Stored::Compact(compact) = stored {
+				self.styles.release(&compact);
+			}
+		}
+	}
+
+	/// Drop every row.
+	pub fn clear(&mut self) {
+		self.deflate();
+
+		for stored in self.rows.drain(..) {
+			if let Stored::Compact(compact) = stored {
+				self.styles.release(&compact);
+			}
+		}
+
+		self.rows.shrink_to_fit();
+		self.size = 0;
+
+		if let Some(spill) = self.spill.as_mut() {
+			if let Err(err) = spill.clear() {
+				error!(target: "cancer::terminal::back", "could not clear spill file: {}", err);
+			}
+		}
+
+		// Nothing refers to the styles anymore.
+		self.styles = Styles::default();
+	}
+
+	/// Get the row at the given index, compact and spilled rows are only there
+	/// once inflated.
+	pub fn get(&self, index: usize) -> Option<&Row> {
+		let spilled = self.spilled();
+
+		if index >= spilled {
+			if let Some(&Stored::Row(ref row)) = self.rows.get(index - spilled) {
+				return Some(row);
+			}
+		}
+
+		self.inflated.get(&index)
+	}
+
+	/// Get the row at the given index, compact and spilled rows that weren't
+	/// inflated are inflated on the spot and thrown away after use.
+	pub fn row(&self, index: usize) -> Option<Cow<Row>> {
+		if let Some(row) = self.get(index) {
+			return Some(Cow::Borrowed(row));
+		}
+
+		let spilled = self.spilled();
+
+		if index < spilled {
+			Some(Cow::Owned(self.unspill(index)))
+		}
+		else if let Some(&Stored::Compact(ref compact)) = self.rows.get(index - spilled) {
+			Some(Cow::Owned(fit(compact.inflate(&self.styles), self.columns)))
+		}
+		else {
+			None
+		}
+	}
+
+	/// Inflate the rows between `start` and `end` exclusive so they can be
+	/// borrowed with `get`, out of bound values are clamped.
+	pub fn inflate(&mut self, start: usize, end: usize) {
+		let spilled = self.spilled();
+		let end     = end.min(self.len());
+
+		for index in start .. end {
+			if self.inflated.contains_key(&index) {
+				continue;
+			}
+
+			let row = if index < spilled {
+				self.unspill(index)
+			}
+			else if let Stored::Compact(ref compact) = self.rows[index - spilled] {
+				fit(compact.inflate(&self.styles), self.columns)
+			}
+			else {
+				continue;
+			};
+
+			self.inflated.insert(index, row);
+		}
+	}
+
+	/// Drop the rows that were inflated for reading.
+	pub fn deflate(&mut self) {
+		self.inflated.clear();
+		self.inflated.shrink_to_fit();
+	}
+
+	/// Read a spilled row, fit to the current width.
+	fn unspill(&self, index: usize) -> Row {
+		let row = self.spill.as_ref().unwrap().get(index).map(|data| Compact::decode(&data));
+
+		let row = match row {
+			Ok(Some(row)) =>
+				row,
+
+			Ok(None) => {
+				error!(target: "cancer::terminal::back", "corrupted spilled row: {}", index);
+				Row { inner: VecDeque::new(), wrapped: false }
+			}
+
+			Err(err) => {
+				error!(target: "cancer::terminal::back", "could not read spill file: {}", err);
+				Row { inner: VecDeque::new(), wrapped: false }
+			}
+		};
+
+		fit(row, self.columns)
+	}
+}
+
+/// Pad the row to the width, or drop the empty cells past it.
+fn fit(mut row: Row, columns: u32) -> Row {
+	let columns = columns as usize;
+
+	if row.len() < columns {
+		row.resize(columns, Cell::default());
+	}
+
+	while row.len() > columns && row.back().map(Cell::is_empty).unwrap_or(false) {
+		row.pop_back();
+	}
+
+	row
+}
+
+impl Stored {
+	fn is_wrapped(&self) -> bool {
+		match *self {
+			Stored::Row(ref row) =>
+				row.is_wrapped(),
+
+			Stored::Compact(ref compact) =>
+				compact.wrapped,
+		}
+	}
+
+	fn size(&self) -> usize {
+		match *self {
+			Stored::Row(ref row) =>
+				row.size(),
+
+			Stored::Compact(ref compact) =>
+				compact.size(),
+		}
+	}
+
+	fn into_row(self, styles: &mut Styles) -> Row {
+		match self {
+			Stored::Row(row) =>
+				row,
+
+			Stored::Compact(compact) => {
+				let row = compact.inflate(styles);
+				styles.release(&compact);
+
+				row
+			}
+		}
+	}
+}
+
+impl Compact {
+	/// Compact the row, returns `None` if it cannot be compacted, when lossy
+	/// images and overly long grapheme clusters are dropped instead.
+	fn new(row: &Row, styles: &mut Styles, lossy: bool) -> Option<Compact> {
+		let mut text    = String::new();
+		let mut spans   = Vec::<Span>::new();
+		let mut lengths = Vec::new();
+		let mut simple  = true;
+		let mut last    = None;
+
+		// Cells mostly share the style of the previous one, so the lookup is
+		// skipped when it's the same.
+		fn intern<'a>(styles: &mut Styles, last: &mut Option<(&'a Rc<Style>, u32)>, style: &'a Rc<Style>) -> u32 {
+			if let Some((previous, index)) = *last {
+				if Rc::ptr_eq(previous, style) {
+					styles.retain(index);
+					return index;
+				}
+			}
+
+			let index = styles.intern(style);
+			*last = Some((style, index));
+
+			index
+		}
+
+		// Check first, so no style is interned for a row that's kept as is.
+		if !lossy && row.iter().any(|cell| match *cell {
+			Cell::Occupied { ref value, .. } =>
+				value.len() > u8::max_value() as usize,
+
+			#[cfg(feature = "images")]
+			Cell::Image { .. } =>
+				true,
+
+			_ =>
+				false,
+		}) {
+			return None;
+		}
+
+		for cell in row.iter() {
+			let (kind, style) = match *cell {
+				Cell::Empty { ref style } =>
+					(Kind::Empty, intern(styles, &mut last, style)),
+
+				Cell::Occupied { ref style, ref value } => {
+					let mut value: &str = value;
+
+					// Overly long grapheme clusters are cut to their first character.
+					if value.len() > u8::max_value() as usize {
+						value = &value[.. value.chars().next().unwrap().len_utf8()];
+					}
+
+					text.push_str(value);
+					lengths.push(value.len() as u8);
+					simple = simple && value.chars().count() == 1;
+
+					(Kind::Occupied, intern(styles, &mut last, style))
+				}
+
+				Cell::Reference(..) =>
+					(Kind::Reference, spans.last().map(|s| s.style).unwrap_or(0)),
+
+				#[cfg(feature = "images")]
+				Cell::Image { ref style, .. } =>
+					(Kind::Empty, intern(styles, &mut last, style)),
+			};
+
+			if let Some(span) = spans.last_mut() {
+				if span.kind == kind && span.style == style && span.count < u16::max_value() {
+					span.count += 1;
+					continue;
+				}
+			}
+
+			spans.push(Span { kind: kind, count: 1, style: style });
+		}
+
+		Some(Compact {
+			text:    text.into_boxed_str(),
+			spans:   spans.into_boxed_slice(),
+			lengths: if simple { None } else { Some(lengths.into_boxed_slice()) },
+			wrapped: row.is_wrapped(),
+		})
+	}
+
+	/// Encode for spilling, the styles are stored inline so spilled rows don't
+	/// keep them interned.
+	fn encode(&self, styles: &Styles, buffer: &mut Vec<u8>) {
+		fn u32(buffer: &mut Vec<u8>, value: u32) {
+			buffer.extend_from_slice(&[value as u8, (value >> 8) as u8, (value >> 16) as u8, (value >> 24) as u8]);
+		}
+
+		fn color(buffer: &mut Vec<u8>, color: &Option<Rgba<f64>>) {
+			if let Some(ref color) = *color {
+				buffer.push(1);
+
+				for &value in &[color.red, color.green, color.blue, color.alpha] {
+					let value = value.to_bits();
+					u32(buffer, value as u32);
+					u32(buffer, (value >> 32) as u32);
+				}
+			}
+			else {
+				buffer.push(0);
+			}
+		}
+
+		buffer.push(self.wrapped as u8);
+
+		u32(buffer, self.text.len() as u32);
+		buffer.extend_from_slice(self.text.as_bytes());
+
+		u32(buffer, self.spans.len() as u32);
+		for span in self.spans.iter() {
+			buffer.push(span.kind as u8);
+			buffer.extend_from_slice(&[span.count as u8, (span.count >> 8) as u8]);
+
+			if span.kind != Kind::Reference {
+				let style = styles.get(span.style);
+
+				color(buffer, &style.foreground);
+				color(buffer, &style.background);
+				buffer.push(style.attributes.bits());
+			}
+		}
+
+		if let Some(ref lengths) = self.lengths {
+			u32(buffer, lengths.len() as u32);
+			buffer.extend_from_slice(lengths);
+		}
+	}
+
+	/// Decode a spilled row.
+	fn decode(mut buffer: &[u8]) -> Option<Row> {
+		fn take<'a>(buffer: &mut &'a [u8], length: usize) -> Option<&'a [u8]> {
+			if buffer.len() < length {
+				return None;
+			}
+
+			let (value, rest) = buffer.split_at(length);
+			*buffer = rest;
+
+			Some(value)
+		}
+
+		fn u32(buffer: &mut &[u8]) -> Option<u32> {
+			take(buffer, 4).map(|v|
+				v[0] as u32 | (v[1] as u32) << 8 | (v[2] as u32) << 16 | (v[3] as u32) << 24)
+		}
+
+		fn color(buffer: &mut &[u8]) -> Option<Option<Rgba<f64>>> {
+			if try!(option take(buffer, 1))[0] == 0 {
+				return Some(None);
+			}
+
+			let mut values = [0.0; 4];
+			for value in &mut values {
+				let low  = try!(option u32(buffer)) as u64;
+				let high = try!(option u32(buffer)) as u64;
+
+				*value = f64::from_bits(low | high << 32);
+			}
+
+			Some(Some(Rgba::new(values[0], values[1], values[2], values[3])))
+		}
+
+		// The styles are only interned for the inflation.
+		let mut styles = Styles::default();
+		let mut last   = 0;
+
+		let wrapped = try!(option take(&mut buffer, 1))[0] != 0;
+
+		let length = try!(option u32(&mut buffer)) as usize;
+		let text   = try!(option str::from_utf8(try!(option take(&mut buffer, length))).ok());
+
+		let mut spans = Vec::new();
+		for _ in 0 .. try!(option u32(&mut buffer)) {
+			let kind = match try!(option take(&mut buffer, 1))[0] {
+				0 => Kind::Empty,
+				1 => Kind::Occupied,
+				2 => Kind::Reference,
+				_ => return None,
+			};
+
+			let count = try!(option take(&mut buffer, 2));
+			let count = count[0] as u16 | (count[1] as u16) << 8;
+
+			let style = if kind == Kind::Reference {
+				last
+			}
+			else {
+				let style = Style {
+					foreground: try!(option color(&mut buffer)),
+					background: try!(option color(&mut buffer)),
+					attributes: Attributes::from_bits_truncate(try!(option take(&mut buffer, 1))[0]),
+				};
+
+				last = styles.intern(&Rc::new(style));
+				last
+			};
+
+			spans.push(Span { kind: kind, count: count, style: style });
+		}
+
+		let lengths = if buffer.is_empty() {
+			None
+		}
+		else {
+			let length = try!(option u32(&mut buffer)) as usize;
+			Some(try!(option take(&mut buffer, length)).to_vec().into_boxed_slice())
+		};
+
+		Some(Compact {
+			text:    text.to_owned().into_boxed_str(),
+			spans:   spans.into_boxed_slice(),
+			lengths: lengths,
+			wrapped: wrapped,
+		}.inflate(&styles))
+	}
+
+	/// Get the approximate memory used.
+	fn size(&self) -> usize {
+		mem::size_of::<Compact>() +
+			self.text.len() +
+			self.spans.len() * mem::size_of::<Span>() +
+			self.lengths.as_ref().map(|l| l.len()).unwrap_or(0)
+	}
+
+	/// Get the number of cells, without the empty ones at the end.
+	fn width(&self) -> usize {
+		let cells = self.spans.iter().map(|s| s.count as usize).sum::<usize>();
+		let empty = self.spans.iter().rev().take_while(|s| s.kind == Kind::Empty)
+			.map(|s| s.count as usize).sum::<usize>();
+
+		cells - empty
+	}
+
+	/// Turn it back into a full row.
+	fn inflate(&self, styles: &Styles) -> Row {
+		let mut cells  = VecDeque::with_capacity(self.spans.iter().map(|s| s.count as usize).sum());
+		let mut offset = 0;
+		let mut index  = 0;
+		let mut last   = 0;
+
+		for span in self.spans.iter() {
+			for _ in 0 .. span.count {
+				match span.kind {
+					Kind::Empty => {
+						cells.push_back(Cell::empty(styles.get(span.style).clone()));
+					}
+
+					Kind::Occupied => {
+						let length = if let Some(ref lengths) = self.lengths {
+							lengths[index] as usize
+						}
+						else {
+							self.text[offset ..].chars().next().map(|c| c.len_utf8()).unwrap_or(0)
+						};
+
+						last = cells.len();
+						cells.push_back(Cell::occupied(self.text[offset .. offset + length].into(), styles.get(span.style).clone()));
+
+						offset += length;
+						index  += 1;
+					}
+
+					Kind::Reference => {
+						let length = cells.len();
+						cells.push_back(Cell::reference((length - last) as u8));
+					}
+				}
+			}
+		}
+
+		Row {
+			inner:   cells,
+			wrapped: self.wrapped,
+		}
+	}
+}
+
+impl Styles {
+	/// Intern the style for a cell, returning its index.
+	fn intern(&mut self, style: &Rc<Style>) -> u32 {
+		let key = Key::from(&**style);
+
+		if let Some(&index) = self.indices.get(&key) {
+			self.retain(index);
+			return index;
+		}
+
+		let index = if let Some(index) = self.free.pop() {
+			self.inner[index as usize] = Some((style.clone(), 1));
+			index
+		}
+		else {
+			self.inner.push(Some((style.clone(), 1)));
+			self.inner.len() as u32 - 1
+		};
+
+		self.indices.insert(key, index);
+		index
+	}
+
+	/// Count another cell using the interned style.
+	fn retain(&mut self, index: u32) {
+		if let Some((_, ref mut count)) = self.inner[index as usize] {
+			*count += 1;
+		}
+	}
+
+	/// Release the styles used by the cells of a dropped row.
+	fn release(&mut self, compact: &Compact) {
+		for span in compact.spans.iter().filter(|s| s.kind != Kind::Reference) {
+			let unused = match self.inner.get_mut(span.style as usize) {
+				Some(&mut Some((_, ref mut count))) => {
+					*count -= span.count as usize;
+					*count == 0
+				}
+
+				_ =>
+					false,
+			};
+
+			if unused {
+				let (style, _) = self.inner[span.style as usize].take().unwrap();
+
+				self.indices.remove(&Key::from(&*style));
+				self.free.push(span.style);
+			}
+		}
+	}
+
+	/// Get the interned style.
+	fn get(&self, index: u32) -> &Rc<Style> {
+		&self.inner[index as usize].as_ref().expect("style not interned").0
+	}
+
+	/// Get the approximate memory used.
+	fn size(&self) -> usize {
+		self.inner.capacity() * mem::size_of::<Option<(Rc<Style>, usize)>>() +
+			self.indices.capacity() * (mem::size_of::<Key>() + mem::size_of::<u32>()) +
+			self.free.capacity() * mem::size_of::<u32>() +
+			self.indices.len() * mem::size_of::<Style>()
+	}
+}
+
+impl<'a> From<&'a Style> for Key {
+	fn from(style: &Style) -> Key {
+		fn color(color: &Option<Rgba<f64>>) -> Option<[u64; 4]> {
+			color.as_ref().map(|c| [c.red.to_bits(), c.green.to_bits(), c.blue.to_bits(), c.alpha.to_bits()])
+		}
+
+		Key(color(&style.foreground), color(&style.background), style.attributes.bits())
+	}
+}
@@ -0,0 +1,109 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+use unicode_bidi::{BidiInfo, BidiClass, Level, bidi_class};
+use terminal::Cell;
+
+/// Mapping between the logical and visual columns of a reordered row.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Map {
+	visual:  Vec<u32>,
+	logical: Vec<u32>,
+}
+
+impl Map {
+	/// Reorder a row of the given width, returns `None` if the display order
+	/// matches the logical order.
+	pub fn new<'a, F>(width: u32, rtl: bool, cell: F) -> Option<Map>
+		where F: Fn(u32) -> &'a Cell
+	{
+		let mut text  = String::new();
+		let mut units = Vec::new();
+		let mut right = rtl;
+
+		for x in 0 .. width {
+			let cell = cell(x);
+
+			if cell.is_reference() {
+				continue;
+			}
+
+			units.push((text.len(), x, cell.width()));
+
+			if cell.is_occupied() {
+				right = right || cell.value().chars().any(|c|
+					match bidi_class(c) { BidiClass::R | BidiClass::AL | BidiClass::AN => true, _ => false });
+
+				text.push_str(cell.value());
+			}
+			else {
+				text.push(' ');
+			}
+		}
+
+		// Fast path for rows without any right to left content.
+		if !right || text.is_empty() {
+			return None;
+		}
+
+		let info = BidiInfo::new(&text, Some(if rtl { Level::rtl() } else { Level::ltr() }));
+		let para = &info.paragraphs[0];
+
+		let (levels, runs) = info.visual_runs(para, para.range.clone());
+		let mut map = Map {
+			visual:  vec![0; width as usize],
+			logical: vec![0; width as usize],
+		};
+
+		let mut column = 0;
+		for run in runs {
+			let mut inside = units.iter()
+				.filter(|&&(offset, _, _)| offset >= run.start && offset < run.end)
+				.cloned().collect::<Vec<_>>();
+
+			if levels[run.start].is_rtl() {
+				inside.reverse();
+			}
+
+			for (_, x, width) in inside {
+				for offset in 0 .. width {
+					if x + offset < map.visual.len() as u32 && column + offset < map.logical.len() as u32 {
+						map.visual[(x + offset) as usize]       = column + offset;
+						map.logical[(column + offset) as usize] = x + offset;
+					}
+				}
+
+				column += width;
+			}
+		}
+
+		if map.visual.iter().enumerate().all(|(x, &v)| x as u32 == v) {
+			return None;
+		}
+
+		Some(map)
+	}
+
+	/// Get the visual column for the given logical column.
+	pub fn visual(&self, x: u32) -> u32 {
+		self.visual.get(x as usize).cloned().unwrap_or(x)
+	}
+
+	/// Get the logical column for the given visual column.
+	pub fn logical(&self, x: u32) -> u32 {
+		self.logical.get(x as usize).cloned().unwrap_or(x)
+	}
+}
@@ -0,0 +1,489 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::borrow::Cow;
+use std::fs::OpenOptions;
+use std::io::{self, Write, BufWriter};
+use std::os::unix::fs::OpenOptionsExt;
+use std::fmt::Write as FmtWrite;
+use std::mem;
+
+use picto::color::Rgba;
+use config::Config;
+use style::{self, Style};
+use terminal::{Grid, Row, Cell};
+use terminal::printer::expand;
+
+/// How captured lines are serialized.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Format {
+	/// Plain text, trailing blanks are trimmed.
+	Text,
+
+	/// Text with SGR sequences to restore the colors and attributes.
+	Ansi,
+
+	/// A preformatted HTML block with the colors and attributes.
+	Html,
+
+	/// An RTF document with the colors and attributes.
+	Rtf,
+}
+
+impl Format {
+	/// Parse a format from its name.
+	pub fn parse<T: AsRef<str>>(name: T) -> Option<Self> {
+		match &*name.as_ref().to_lowercase() {
+			"text" | "plain" => Some(Format::Text),
+			"ansi"           => Some(Format::Ansi),
+			"html"           => Some(Format::Html),
+			"rtf"            => Some(Format::Rtf),
+			_                => None,
+		}
+	}
+}
+
+/// Serialize the lines between `start` and `end` inclusive.
+///
+/// Lines are numbered like the rows in the view, negative ones are in the
+/// scroll back and out of bound values are clamped, rows that wrapped are
+/// joined in a single line.
+pub fn capture(config: &Config, grid: &Grid, start: i64, end: i64, format: Format) -> String {
+	let mut output = Vec::new();
+	write(config, grid, start, end, format, &mut output).unwrap();
+
+	String::from_utf8(output).unwrap()
+}
+
+/// Serialize the whole scroll back and view.
+pub fn all(config: &Config, grid: &Grid, format: Format) -> String {
+	capture(config, grid, i64::min_value(), i64::max_value(), format)
+}
+
+/// Save the whole scroll back and view to the given path.
+pub fn save(config: &Config, grid: &Grid, path: &str, format: Format) -> io::Result<()> {
+	// The capture can contain anything that went through the terminal.
+	let file = OpenOptions::new().write(true).create(true).truncate(true).mode(0o600).open(expand(path))?;
+	let mut output = BufWriter::new(file);
+
+	write(config, grid, i64::min_value(), i64::max_value(), format, &mut output)?;
+	output.flush()
+}
+
+/// Serialize the lines between `start` and `end` inclusive to the output, like
+/// `capture`.
+///
+/// It goes a line at a time, so the rows in the scroll back are inflated and
+/// dropped one after the other instead of all at once.
+pub fn write<W: Write>(config: &Config, grid: &Grid, start: i64, end: i64, format: Format, mut output: W) -> io::Result<()> {
+	let mut buffer = String::new();
+	let mut table  = Vec::new();
+	let mut first  = true;
+
+	match format {
+		Format::Text | Format::Ansi =>
+			(),
+
+		Format::Html =>
+			html_start(config, &mut buffer),
+
+		// The color table comes first, so the colors are collected beforehand.
+		Format::Rtf => {
+			table = rtf_table(config);
+
+			each(grid, start, end, |line| {
+				for (style, _) in runs(line) {
+					let (foreground, background) = colors(config, &style);
+					index(&mut table, foreground);
+					index(&mut table, background);
+				}
+
+				Ok(())
+			})?;
+
+			rtf_start(&table, &mut buffer);
+		}
+	}
+
+	each(grid, start, end, |line| {
+		match format {
+			Format::Text => text_line(&mut buffer, line),
+			Format::Ansi => ansi_line(&mut buffer, line),
+			Format::Html => html_line(config, &mut buffer, line, first),
+			Format::Rtf  => rtf_line(config, &mut table, &mut buffer, line),
+		}
+
+		first = false;
+		output.write_all(buffer.as_bytes())?;
+		buffer.clear();
+
+		Ok(())
+	})?;
+
+	match format {
+		Format::Text | Format::Ansi =>
+			(),
+
+		Format::Html =>
+			html_end(&mut buffer),
+
+		Format::Rtf =>
+			rtf_end(&mut buffer),
+	}
+
+	output.write_all(buffer.as_bytes())
+}
+
+/// Call the closure with the cells of each line between `start` and `end`
+/// inclusive, joining the rows that wrapped.
+fn each<F>(grid: &Grid, start: i64, end: i64, mut block: F) -> io::Result<()>
+	where F: FnMut(&[&Cell]) -> io::Result<()>
+{
+	let start = start.max(-(grid.back().len() as i64));
+	let end   = end.min(grid.view().len() as i64 - 1);
+
+	let mut rows    = Vec::new();
+	let mut current = row(grid, start);
+
+	for y in start ..= end {
+		let next = if y < end { row(grid, y + 1) } else { None };
+
+		if let Some(row) = current.take() {
+			rows.push(row);
+		}
+
+		// A wrapped row continues the previous one, so the line ends when the
+		// next row doesn't.
+		if !next.as_ref().map(|r| r.is_wrapped()).unwrap_or(false) {
+			{
+				let line = rows.iter().flat_map(|r| r.iter()).collect::<Vec<_>>();
+				block(&line)?;
+			}
+
+			rows.clear();
+		}
+
+		current = next;
+	}
+
+	Ok(())
+}
+
+/// Serialize the lines as plain text.
+pub fn text(lines: &[Vec<&Cell>]) -> String {
+	let mut output = String::new();
+
+	for line in lines {
+		text_line(&mut output, line);
+	}
+
+	output
+}
+
+fn text_line(output: &mut String, line: &[&Cell]) {
+	let text = line.iter().map(|c| c.value()).collect::<String>();
+
+	output.push_str(text.trim_right());
+	output.push('\n');
+}
+
+/// Serialize the lines as text with SGR sequences.
+pub fn ansi(lines: &[Vec<&Cell>]) -> String {
+	let mut output = String::new();
+
+	for line in lines {
+		ansi_line(&mut output, line);
+	}
+
+	output
+}
+
+fn ansi_line(output: &mut String, line: &[&Cell]) {
+	let mut current = Style::default();
+
+	for (style, text) in runs(line) {
+		if style != current {
+			sgr(output, &style);
+			current = style;
+		}
+
+		output.push_str(&text);
+	}
+
+	if current != Style::default() {
+		output.push_str("\x1B[0m");
+	}
+
+	output.push('\n');
+}
+
+/// Serialize the lines as a preformatted HTML block.
+pub fn html(config: &Config, lines: &[Vec<&Cell>]) -> String {
+	let mut output = String::new();
+	html_start(config, &mut output);
+
+	for (i, line) in lines.iter().enumerate() {
+		html_line(config, &mut output, line, i == 0);
+	}
+
+	html_end(&mut output);
+	output
+}
+
+fn html_start(config: &Config, output: &mut String) {
+	let (fg, bg) = colors(config, &Style::default());
+
+	let _ = write!(output, "<pre style=\"font-family: monospace; color: {}; background-color: {};\">",
+		hex(&fg), hex(&bg));
+}
+
+fn html_line(config: &Config, output: &mut String, line: &[&Cell], first: bool) {
+	let (fg, bg) = colors(config, &Style::default());
+
+	if !first {
+		output.push('\n');
+	}
+
+	for (style, text) in runs(line) {
+		let mut text = text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+
+		if style != Style::default() {
+			let (foreground, background) = colors(config, &style);
+			let mut css = String::new();
+
+			if foreground != fg {
+				let _ = write!(css, "color: {}; ", hex(&foreground));
+			}
+
+			if background != bg {
+				let _ = write!(css, "background-color: {}; ", hex(&background));
+			}
+
+			if style.attributes().contains(style::BOLD) {
+				css.push_str("font-weight: bold; ");
+			}
+
+			if style.attributes().contains(style::FAINT) {
+				css.push_str("opacity: 0.5; ");
+			}
+
+			if style.attributes().contains(style::ITALIC) {
+				css.push_str("font-style: italic; ");
+			}
+
+			match (style.attributes().contains(style::UNDERLINE), style.attributes().contains(style::STRUCK)) {
+				(true, true)  => css.push_str("text-decoration: underline line-through; "),
+				(true, false) => css.push_str("text-decoration: underline; "),
+				(false, true) => css.push_str("text-decoration: line-through; "),
+				_             => (),
+			}
+
+			if !css.is_empty() {
+				text = format!("<span style=\"{}\">{}</span>", css.trim_right(), text);
+			}
+		}
+
+		output.push_str(&text);
+	}
+}
+
+fn html_end(output: &mut String) {
+	output.push_str("</pre>");
+}
+
+/// Serialize the lines as an RTF document.
+///
+/// The color table starts with the default colors followed by the 16 base
+/// colors of the palette, any other color is appended as it's found.
+pub fn rtf(config: &Config, lines: &[Vec<&Cell>]) -> String {
+	let mut table = rtf_table(config);
+	let mut body  = String::new();
+
+	for line in lines {
+		rtf_line(config, &mut table, &mut body, line);
+	}
+
+	let mut output = String::new();
+	rtf_start(&table, &mut output);
+	output.push_str(&body);
+	rtf_end(&mut output);
+
+	output
+}
+
+fn rtf_table(config: &Config) -> Vec<Rgba<f64>> {
+	let (fg, bg)  = colors(config, &Style::default());
+	let mut table = vec![fg, bg];
+	table.extend((0 .. 16).map(|i| *config.color().get(i)));
+
+	table
+}
+
+fn rtf_start(table: &[Rgba<f64>], output: &mut String) {
+	output.push_str("{\\rtf1\\ansi\\deff0{\\fonttbl{\\f0\\fmodern monospace;}}\n{\\colortbl;");
+
+	for color in table {
+		let (r, g, b) = rgb(color);
+		let _ = write!(output, "\\red{}\\green{}\\blue{};", r, g, b);
+	}
+
+	output.push_str("}\n\\f0\\cb2\\cf1\n");
+}
+
+fn rtf_line(config: &Config, table: &mut Vec<Rgba<f64>>, body: &mut String, line: &[&Cell]) {
+	for (style, text) in runs(line) {
+		let (foreground, background) = colors(config, &style);
+		let _ = write!(body, "{{\\cf{}\\highlight{}", index(table, foreground), index(table, background));
+
+		for &(flag, code) in &[(style::BOLD, "\\b"), (style::ITALIC, "\\i"),
+		                       (style::UNDERLINE, "\\ul"), (style::STRUCK, "\\strike")]
+		{
+			if style.attributes().contains(flag) {
+				body.push_str(code);
+			}
+		}
+
+		body.push(' ');
+
+		for ch in text.chars() {
+			match ch {
+				'\\' | '{' | '}' => {
+					body.push('\\');
+					body.push(ch);
+				}
+
+				_ if ch >= ' ' && ch <= '~' =>
+					body.push(ch),
+
+				_ => {
+					let mut buffer = [0u16; 2];
+
+					for unit in ch.encode_utf16(&mut buffer) {
+						let _ = write!(body, "\\u{}?", *unit as i16);
+					}
+				}
+			}
+		}
+
+		body.push('}');
+	}
+
+	body.push_str("\\line\n");
+}
+
+fn rtf_end(output: &mut String) {
+	output.push('}');
+}
+
+/// Get the row at the given line, if it's there.
+fn row(grid: &Grid, y: i64) -> Option<Cow<Row>> {
+	if y < 0 {
+		grid.back().row((grid.back().len() as i64 + y) as usize)
+	}
+	else {
+		grid.view().get(y as usize).map(Cow::Borrowed)
+	}
+}
+
+/// Split the line in runs of text with the same style, cells past the last
+/// one that would be visible are dropped.
+fn runs(line: &[&Cell]) -> Vec<(Style, String)> {
+	let end = line.iter().rposition(|c| !c.is_default()).map(|i| i + 1).unwrap_or(0);
+	let mut runs = Vec::<(Style, String)>::new();
+
+	for cell in &line[.. end] {
+		if cell.is_reference() {
+			continue;
+		}
+
+		match runs.last_mut() {
+			Some(&mut (ref style, ref mut text)) if style == &**cell.style() => {
+				text.push_str(cell.value());
+				continue;
+			}
+
+			_ => ()
+		}
+
+		runs.push((**cell.style(), cell.value().into()));
+	}
+
+	runs
+}
+
+/// Resolve the colors a style is drawn with.
+fn colors(config: &Config, style: &Style) -> (Rgba<f64>, Rgba<f64>) {
+	let mut fg = *style.foreground().unwrap_or_else(|| config.style().color().foreground());
+	let mut bg = *style.background().unwrap_or_else(|| config.style().color().background());
+
+	if style.attributes().contains(style::REVERSE) {
+		mem::swap(&mut fg, &mut bg);
+	}
+
+	if style.attributes().contains(style::INVISIBLE) {
+		fg = bg;
+	}
+
+	(fg, bg)
+}
+
+/// Get the position of the color in the RTF color table, adding it if missing.
+fn index(table: &mut Vec<Rgba<f64>>, color: Rgba<f64>) -> usize {
+	if let Some(index) = table.iter().position(|c| *c == color) {
+		return index + 1;
+	}
+
+	table.push(color);
+	table.len()
+}
+
+fn rgb(color: &Rgba<f64>) -> (u8, u8, u8) {
+	((color.red * 255.0).round() as u8,
+	 (color.green * 255.0).round() as u8,
+	 (color.blue * 255.0).round() as u8)
+}
+
+fn hex(color: &Rgba<f64>) -> String {
+	let (r, g, b) = rgb(color);
+	format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// Write the SGR sequence that turns the default style into the given one.
+fn sgr(output: &mut String, style: &Style) {
+	output.push_str("\x1B[0");
+
+	for &(flag, code) in &[(style::BOLD, 1), (style::FAINT, 2), (style::ITALIC, 3),
+	                       (style::UNDERLINE, 4), (style::BLINK, 5), (style::REVERSE, 7),
+	                       (style::INVISIBLE, 8), (style::STRUCK, 9)]
+	{
+		if style.attributes().contains(flag) {
+			let _ = write!(output, ";{}", code);
+		}
+	}
+
+	if let Some(color) = style.foreground() {
+		let (r, g, b) = rgb(color);
+		let _ = write!(output, ";38;2;{};{};{}", r, g, b);
+	}
+
+	if let Some(color) = style.background() {
+		let (r, g, b) = rgb(color);
+		let _ = write!(output, ";48;2;{};{};{}", r, g, b);
+	}
+
+	output.push('m');
+}
@@ -22,6 +22,7 @@ use unicode_width::UnicodeWidthStr;
 use tendril::StrTendril;
 
 use style::Style;
+#[cfg(feature = "images")]
 use sys::cairo;
 
 #[derive(PartialEq, Clone, Debug)]
@@ -30,6 +31,7 @@ pub enum Cell {
 		style: Rc<Style>,
 	},
 
+	#[cfg(feature = "images")]
 	Image {
 		style:  Rc<Style>,
 		buffer: Box<cairo::Image>,
@@ -124,18 +126,23 @@ impl Cell {
 
 	/// Check if the cell is an image.
 	pub fn is_image(&self) -> bool {
-		if let Cell::Image { .. } = *self {
-			true
-		}
-		else {
-			false
+		match *self {
+			#[cfg(feature = "images")]
+			Cell::Image { .. } =>
+				true,
+
+			_ =>
+				false
 		}
 	}
 
 	/// Check if the cell is wide.
 	pub fn is_wide(&self) -> bool {
 		match *self {
-			Cell::Empty { .. } |
+			Cell::Empty { .. } =>
+				false,
+
+			#[cfg(feature = "images")]
 			Cell::Image { .. } =>
 				false,
 
@@ -168,6 +175,7 @@ impl Cell {
 	}
 
 	/// Make the cell into an image.
+	#[cfg(feature = "images")]
 	pub fn make_image(&mut self, buffer: cairo::Image, style: Rc<Style>) {
 		if let Cell::Image { ref buffer, .. } = *self {
 			if buffer.as_ref() == buffer.as_ref() {
@@ -185,10 +193,13 @@ impl Cell {
 	pub fn set_style(&mut self, value: Rc<Style>) {
 		match *self {
 			Cell::Empty { ref mut style, .. } |
-			Cell::Image { ref mut style, .. } |
 			Cell::Occupied { ref mut style, .. } =>
 				*style = value,
 
+			#[cfg(feature = "images")]
+			Cell::Image { ref mut style, .. } =>
+				*style = value,
+
 			Cell::Reference(..) =>
 				()
 		}
@@ -198,10 +209,13 @@ impl Cell {
 	pub fn style(&self) -> &Rc<Style> {
 		match *self {
 			Cell::Empty { ref style, .. } |
-			Cell::Image { ref style, .. } |
 			Cell::Occupied { ref style, .. } =>
 				style,
 
+			#[cfg(feature = "images")]
+			Cell::Image { ref style, .. } =>
+				style,
+
 			Cell::Reference(..) =>
 				unreachable!(),
 		}
@@ -216,16 +230,38 @@ impl Cell {
 			Cell::Occupied { ref value, .. } =>
 				value.as_ref(),
 
-			Cell::Reference(..) |
+			Cell::Reference(..) =>
+				"",
+
+			#[cfg(feature = "images")]
 			Cell::Image { .. } =>
 				"",
 		}
 	}
 
+	/// Get the approximate memory used by the cell.
+	pub fn size(&self) -> usize {
+		mem::size_of::<Cell>() + match *self {
+			Cell::Occupied { ref value, .. } =>
+				value.len(),
+
+			#[cfg(feature = "images")]
+			Cell::Image { ref buffer, .. } =>
+				(buffer.stride() * buffer.height()) as usize,
+
+			Cell::Empty { .. } |
+			Cell::Reference(..) =>
+				0,
+		}
+	}
+
 	/// Get the cell width.
 	pub fn width(&self) -> u32 {
 		match *self {
-			Cell::Empty { .. } |
+			Cell::Empty { .. } =>
+				1,
+
+			#[cfg(feature = "images")]
 			Cell::Image { .. } =>
 				1,
 
@@ -244,13 +280,17 @@ impl Cell {
 				offset as u32,
 
 			Cell::Empty { .. } |
-			Cell::Occupied { .. } |
+			Cell::Occupied { .. } =>
+				unreachable!(),
+
+			#[cfg(feature = "images")]
 			Cell::Image { .. } =>
 				unreachable!()
 		}
 	}
 
 	/// Get the image buffer.
+	#[cfg(feature = "images")]
 	pub fn image(&self) -> &cairo::Image {
 		match *self {
 			Cell::Image { ref buffer, .. } =>
@@ -283,6 +323,11 @@ impl<'a> Position<'a> {
 	pub fn y(&self) -> u32 {
 		self.y
 	}
+
+	/// Get the same cell at a different X, used for display reordering.
+	pub fn at(self, x: u32) -> Position<'a> {
+		Position { x: x, .. self }
+	}
 }
 
 impl<'a> Deref for Position<'a> {
@@ -0,0 +1,259 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+use control::DEC;
+use control::DEC::charset::{DEC as Dec, NRCS};
+
+/// DEC Special Graphics, from `0x5F` to `0x7E`.
+const GRAPHIC: [char; 32] = [
+	' ', '◆', '▒', '␉', '␌', '␍', '␊', '°', '±', '␤', '␋', '┘', '┐', '┌', '└', '┼',
+	'⎺', '⎻', '─', '⎼', '⎽', '├', '┤', '┴', '┬', '│', '≤', '≥', 'π', '≠', '£', '·',
+];
+
+/// DEC Technical, from `0x21` to `0x7E`, `None` means the position is
+/// undefined and the character is left untouched.
+const TECHNICAL: [Option<char>; 94] = [
+	          Some('⎷'), Some('┌'), Some('─'), Some('⌠'), Some('⌡'), Some('│'), Some('⎡'),
+	Some('⎣'), Some('⎤'), Some('⎦'), Some('⎛'), Some('⎝'), Some('⎞'), Some('⎠'), Some('⎨'),
+	Some('⎬'), None,      None,      None,      None,      None,      None,      None,
+	None,      None,      None,      None,      Some('≤'), Some('≠'), Some('≥'), Some('∫'),
+	Some('∴'), Some('∝'), Some('∞'), Some('÷'), Some('Δ'), Some('∇'), Some('Φ'), Some('Γ'),
+	Some('∼'), Some('≃'), Some('Θ'), Some('×'), Some('Λ'), Some('⇔'), Some('⇒'), Some('≡'),
+	Some('Π'), Some('Ψ'), None,      Some('Σ'), None,      None,      Some('√'), Some('Ω'),
+	Some('Ξ'), Some('Υ'), Some('⊂'), Some('⊃'), Some('∩'), Some('∪'), Some('∧'), Some('∨'),
+	Some('¬'), Some('α'), Some('β'), Some('χ'), Some('δ'), Some('ε'), Some('φ'), Some('γ'),
+	Some('η'), Some('ι'), Some('θ'), Some('κ'), Some('λ'), None,      Some('ν'), Some('∂'),
+	Some('π'), Some('ψ'), Some('ρ'), Some('σ'), Some('τ'), None,      Some('ƒ'), Some('ω'),
+	Some('ξ'), Some('υ'), Some('ζ'), Some('←'), Some('↑'), Some('→'), Some('↓'),
+];
+
+/// The positions national replacement character sets can replace.
+const NATIONAL: [char; 12] = ['#', '@', '[', '\\', ']', '^', '_', '`', '{', '|', '}', '~'];
+
+/// Replacements for each NRCS, in the same order as `NATIONAL`, `None` means
+/// the ASCII character is kept.
+fn national(set: NRCS) -> [Option<char>; 12] {
+	match set {
+		NRCS::UK =>
+			[Some('£'), None, None, None, None, None, None, None, None, None, None, None],
+
+		NRCS::Finnish =>
+			[None, None, Some('Ä'), Some('Ö'), Some('Å'), Some('Ü'), None, Some('é'), Some('ä'), Some('ö'), Some('å'), Some('ü')],
+
+		NRCS::French =>
+			[Some('£'), Some('à'), Some('°'), Some('ç'), Some('§'), None, None, None, Some('é'), Some('ù'), Some('è'), Some('¨')],
+
+		NRCS::FrenchCanadian =>
+			[None, Some('à'), Some('â'), Some('ç'), Some('ê'), Some('î'), None, Some('ô'), Some('é'), Some('ù'), Some('è'), Some('û')],
+
+		NRCS::German =>
+			[None, Some('§'), Some('Ä'), Some('Ö'), Some('Ü'), None, None, None, Some('ä'), Some('ö'), Some('ü'), Some('ß')],
+
+		NRCS::Italian =>
+			[Some('£'), Some('§'), Some('°'), Some('ç'), Some('é'), None, None, Some('ù'), Some('à'), Some('ò'), Some('è'), Some('ì')],
+
+		NRCS::Norwegian =>
+			[None, Some('Ä'), Some('Æ'), Some('Ø'), Some('Å'), Some('Ü'), None, Some('ä'), Some('æ'), Some('ø'), Some('å'), Some('ü')],
+
+		NRCS::Portuguese =>
+			[None, None, Some('Ã'), Some('Ç'), Some('Õ'), None, None, None, Some('ã'), Some('ç'), Some('õ'), None],
+
+		NRCS::Spanish =>
+			[Some('£'), Some('§'), Some('¡'), Some('Ñ'), Some('¿'), None, None, None, Some('°'), Some('ñ'), Some('ç'), None],
+
+		NRCS::Swedish =>
+			[None, Some('É'), Some('Ä'), Some('Ö'), Some('Å'), Some('Ü'), None, Some('é'), Some('ä'), Some('ö'), Some('å'), Some('ü')],
+
+		NRCS::Swiss =>
+			[Some('ù'), Some('à'), Some('é'), Some('ç'), Some('ê'), Some('î'), Some('è'), Some('ô'), Some('ä'), Some('ö'), Some('ü'), Some('û')],
+
+		NRCS::Turkish =>
+			[None, Some('İ'), Some('Ş'), Some('Ö'), Some('Ç'), Some('Ü'), None, Some('Ğ'), Some('ş'), Some('ö'), Some('ç'), Some('ü')],
+
+		NRCS::SCS =>
+			[None, Some('Ž'), Some('Š'), Some('Đ'), Some('Ć'), Some('Č'), None, Some('ž'), Some('š'), Some('đ'), Some('ć'), Some('č')],
+
+		// These replace whole ranges of letters, see `translate`.
+		NRCS::Greek | NRCS::Hebrew | NRCS::Russian =>
+			[None; 12],
+	}
+}
+
+/// KOI-8 Cyrillic, from `0x40` to `0x7E`.
+const CYRILLIC: [char; 63] = [
+	'ю', 'а', 'б', 'ц', 'д', 'е', 'ф', 'г', 'х', 'и', 'й', 'к', 'л', 'м', 'н', 'о',
+	'п', 'я', 'р', 'с', 'т', 'у', 'ж', 'в', 'ь', 'ы', 'з', 'ш', 'э', 'щ', 'ч', 'ъ',
+	'Ю', 'А', 'Б', 'Ц', 'Д', 'Е', 'Ф', 'Г', 'Х', 'И', 'Й', 'К', 'Л', 'М', 'Н', 'О',
+	'П', 'Я', 'Р', 'С', 'Т', 'У', 'Ж', 'В', 'Ь', 'Ы', 'З', 'Ш', 'Э', 'Щ', 'Ч',
+];
+
+/// Offset a code point into the given Unicode block.
+fn offset(ch: char, from: char, to: u32) -> char {
+	::std::char::from_u32(to + (ch as u32 - from as u32)).unwrap_or(ch)
+}
+
+/// Translate a character from its GL code point to the character it stands
+/// for in the given charset.
+pub fn translate(charset: DEC::Charset, ch: char) -> char {
+	// Only the 94 graphic characters are affected by designations.
+	if ch < '\x21' || ch > '\x7E' {
+		return ch;
+	}
+
+	match charset {
+		DEC::Charset::DEC(Dec::Graphic) => {
+			if ch >= '\x5F' {
+				GRAPHIC[ch as usize - 0x5F]
+			}
+			else {
+				ch
+			}
+		}
+
+		DEC::Charset::DEC(Dec::Technical) =>
+			TECHNICAL[ch as usize - 0x21].unwrap_or(ch),
+
+		// The supplemental set is mostly the upper half of Latin-1, with a few
+		// differences.
+		DEC::Charset::DEC(Dec::Supplemental) => match ch {
+			'(' => '¤',
+			'W' => 'Œ',
+			']' => 'Ÿ',
+			'w' => 'œ',
+			'}' => 'ÿ',
+			ch  => (ch as u8 + 0x80) as char,
+		},
+
+		// The user preferred supplemental set is Latin-1.
+		DEC::Charset::UserPreferred =>
+			(ch as u8 + 0x80) as char,
+
+		// The national supplemental sets follow the ISO 8859 part they were
+		// standardized as, so Greek is 8859-7, Hebrew is 8859-8 and Turkish is
+		// 8859-9.
+		DEC::Charset::DEC(Dec::Greek) => match ch {
+			'R'            => ch,
+			_ if ch >= 'A' => offset(ch, 'A', 0x391),
+			_              => (ch as u8 + 0x80) as char,
+		},
+
+		DEC::Charset::DEC(Dec::Hebrew) => match ch {
+			_ if ch >= '`' && ch <= 'z' => offset(ch, '`', 0x5D0),
+			_ if ch >= '`'              => ch,
+			_                           => (ch as u8 + 0x80) as char,
+		},
+
+		DEC::Charset::DEC(Dec::Turkish) => match ch {
+			'P' => 'Ğ',
+			']' => 'İ',
+			'^' => 'Ş',
+			'p' => 'ğ',
+			'}' => 'ı',
+			'~' => 'ş',
+			ch  => translate(Dec::Supplemental.into(), ch),
+		},
+
+		DEC::Charset::DEC(Dec::Cyrillic) => {
+			if ch >= '\x40' {
+				CYRILLIC[ch as usize - 0x40]
+			}
+			else {
+				ch
+			}
+		}
+
+		// The 7-bit Greek, Hebrew and Russian sets replace the lower case
+		// letters.
+		DEC::Charset::NRCS(NRCS::Greek) => match ch {
+			_ if ch >= 'a' && ch <= 'q' => offset(ch, 'a', 0x391),
+			_ if ch >= 'r' && ch <= 'x' => offset(ch, 'r', 0x3A3),
+			_                           => ch,
+		},
+
+		DEC::Charset::NRCS(NRCS::Hebrew) => match ch {
+			_ if ch >= '`' && ch <= 'z' => offset(ch, '`', 0x5D0),
+			_                           => ch,
+		},
+
+		DEC::Charset::NRCS(NRCS::Russian) => {
+			if ch >= '`' {
+				CYRILLIC[ch as usize - 0x40]
+			}
+			else {
+				ch
+			}
+		}
+
+		DEC::Charset::NRCS(NRCS::Turkish) if ch == '&' =>
+			'ğ',
+
+		DEC::Charset::NRCS(set) => {
+			if let Some(i) = NATIONAL.iter().position(|&c| c == ch) {
+				national(set)[i].unwrap_or(ch)
+			}
+			else {
+				ch
+			}
+		}
+
+		// The 94 GL characters of ISO sets are the same as ASCII.
+		DEC::Charset::ISO(..) =>
+			ch,
+	}
+}
+
+/// Get the designation for the charset and whether it's a 96 character set,
+/// as used in reports.
+pub fn designation(charset: DEC::Charset) -> (&'static str, bool) {
+	use control::DEC::charset::ISO;
+
+	match charset {
+		DEC::Charset::DEC(Dec::Graphic)      => ("0", false),
+		DEC::Charset::DEC(Dec::Technical)    => (">", false),
+		DEC::Charset::DEC(Dec::Supplemental) => ("%5", false),
+		DEC::Charset::DEC(Dec::Greek)        => ("\"?", false),
+		DEC::Charset::DEC(Dec::Hebrew)       => ("\"4", false),
+		DEC::Charset::DEC(Dec::Turkish)      => ("%0", false),
+		DEC::Charset::DEC(Dec::Cyrillic)     => ("&4", false),
+		DEC::Charset::UserPreferred          => ("<", false),
+
+		DEC::Charset::NRCS(NRCS::UK)             => ("A", false),
+		DEC::Charset::NRCS(NRCS::Finnish)        => ("C", false),
+		DEC::Charset::NRCS(NRCS::French)         => ("R", false),
+		DEC::Charset::NRCS(NRCS::FrenchCanadian) => ("Q", false),
+		DEC::Charset::NRCS(NRCS::German)         => ("K", false),
+		DEC::Charset::NRCS(NRCS::Italian)        => ("Y", false),
+		DEC::Charset::NRCS(NRCS::Norwegian)      => ("E", false),
+		DEC::Charset::NRCS(NRCS::Portuguese)     => ("%6", false),
+		DEC::Charset::NRCS(NRCS::Spanish)        => ("Z", false),
+		DEC::Charset::NRCS(NRCS::Swedish)        => ("H", false),
+		DEC::Charset::NRCS(NRCS::Swiss)          => ("=", false),
+		DEC::Charset::NRCS(NRCS::Greek)          => ("\">", false),
+		DEC::Charset::NRCS(NRCS::Hebrew)         => ("%=", false),
+		DEC::Charset::NRCS(NRCS::Turkish)        => ("%2", false),
+		DEC::Charset::NRCS(NRCS::SCS)            => ("%3", false),
+		DEC::Charset::NRCS(NRCS::Russian)        => ("&5", false),
+
+		// `ESC ( B` ends up as Latin-2, which is reported back as US-ASCII.
+		DEC::Charset::ISO(ISO::Latin2)   => ("B", false),
+		DEC::Charset::ISO(ISO::Latin1)   => ("A", true),
+		DEC::Charset::ISO(ISO::Greek)    => ("F", true),
+		DEC::Charset::ISO(ISO::Hebrew)   => ("H", true),
+		DEC::Charset::ISO(ISO::Cyrillic) => ("L", true),
+		DEC::Charset::ISO(ISO::Latin5)   => ("M", true),
+	}
+}
@@ -43,6 +43,8 @@ pub struct Cursor {
 
 	pub charsets: [DEC::Charset; 4],
 	pub charset:  u8,
+	pub single:   Option<u8>,
+	pub soft:     [Option<usize>; 4],
 
 	pub foreground: Rgba<f64>,
 	pub background: Rgba<f64>,
@@ -99,6 +101,8 @@ impl Cursor {
 
 			charsets: [DEC::charset::ISO::Latin2.into(); 4],
 			charset:  0,
+			single:   None,
+			soft:     [None; 4],
 
 			foreground: *config.style().cursor().foreground(),
 			background: *config.style().cursor().background(),
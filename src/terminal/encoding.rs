@@ -0,0 +1,241 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::io::{self, Write};
+
+/// The encoding used to talk with the program running in the terminal.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Encoding {
+	Utf8,
+	Latin1,
+	Latin9,
+	Cp437,
+}
+
+impl Default for Encoding {
+	fn default() -> Self {
+		Encoding::Utf8
+	}
+}
+
+/// CP437 from `0x80` to `0xFF`.
+const CP437: [char; 128] = [
+	'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å',
+	'É', 'æ', 'Æ', 'ô', 'ö', 'ò', 'û', 'ù', 'ÿ', 'Ö', 'Ü', '¢', '£', '¥', '₧', 'ƒ',
+	'á', 'í', 'ó', 'ú', 'ñ', 'Ñ', 'ª', 'º', '¿', '⌐', '¬', '½', '¼', '¡', '«', '»',
+	'░', '▒', '▓', '│', '┤', '╡', '╢', '╖', '╕', '╣', '║', '╗', '╝', '╜', '╛', '┐',
+	'└', '┴', '┬', '├', '─', '┼', '╞', '╟', '╚', '╔', '╩', '╦', '╠', '═', '╬', '╧',
+	'╨', '╤', '╥', '╙', '╘', '╒', '╓', '╫', '╪', '┘', '┌', '█', '▄', '▌', '▐', '▀',
+	'α', 'ß', 'Γ', 'π', 'Σ', 'σ', 'µ', 'τ', 'Φ', 'Θ', 'Ω', 'δ', '∞', 'φ', 'ε', '∩',
+	'≡', '±', '≥', '≤', '⌠', '⌡', '÷', '≈', '°', '∙', '·', '√', 'ⁿ', '²', '■', '\u{a0}',
+];
+
+/// The ISO-8859-15 positions that differ from ISO-8859-1.
+const LATIN9: [(u8, char); 8] = [
+	(0xA4, '€'), (0xA6, 'Š'), (0xA8, 'š'), (0xB4, 'Ž'),
+	(0xB8, 'ž'), (0xBC, 'Œ'), (0xBD, 'œ'), (0xBE, 'Ÿ'),
+];
+
+impl Encoding {
+	/// Parse an encoding name.
+	pub fn parse<T: AsRef<str>>(name: T) -> Option<Self> {
+		match &*name.as_ref().to_lowercase() {
+			"utf-8" | "utf8" =>
+				Some(Encoding::Utf8),
+
+			"iso-8859-1" | "latin1" | "latin-1" =>
+				Some(Encoding::Latin1),
+
+			"iso-8859-15" | "latin9" | "latin-9" =>
+				Some(Encoding::Latin9),
+
+			"cp437" | "ibm437" =>
+				Some(Encoding::Cp437),
+
+			_ =>
+				None
+		}
+	}
+
+	/// The name of the encoding.
+	pub fn name(&self) -> &'static str {
+		match *self {
+			Encoding::Utf8   => "UTF-8",
+			Encoding::Latin1 => "ISO-8859-1",
+			Encoding::Latin9 => "ISO-8859-15",
+			Encoding::Cp437  => "CP437",
+		}
+	}
+
+	/// The encoding that follows when cycling through them.
+	pub fn next(&self) -> Self {
+		match *self {
+			Encoding::Utf8   => Encoding::Latin1,
+			Encoding::Latin1 => Encoding::Latin9,
+			Encoding::Latin9 => Encoding::Cp437,
+			Encoding::Cp437  => Encoding::Utf8,
+		}
+	}
+
+	/// Check if the encoding is UTF-8.
+	pub fn is_unicode(&self) -> bool {
+		*self == Encoding::Utf8
+	}
+
+	/// Check if bytes from `0x80` to `0x9F` are 8-bit C1 controls.
+	pub fn controls(&self) -> bool {
+		match *self {
+			Encoding::Latin1 | Encoding::Latin9 =>
+				true,
+
+			Encoding::Utf8 | Encoding::Cp437 =>
+				false,
+		}
+	}
+
+	/// Get how many bytes from the start of the input are printable characters.
+	pub fn printable(&self, input: &[u8]) -> usize {
+		input.iter().take_while(|&&b|
+			b >= 0x20 && b != 0x7F && !(self.controls() && b >= 0x80 && b < 0xA0)).count()
+	}
+
+	/// Decode a single byte, not valid for UTF-8.
+	pub fn decode(&self, byte: u8) -> char {
+		match *self {
+			Encoding::Utf8 =>
+				unreachable!(),
+
+			Encoding::Latin1 =>
+				byte as char,
+
+			Encoding::Latin9 =>
+				LATIN9.iter().find(|&&(b, _)| b == byte).map(|&(_, c)| c).unwrap_or(byte as char),
+
+			Encoding::Cp437 if byte >= 0x80 =>
+				CP437[byte as usize - 0x80],
+
+			Encoding::Cp437 =>
+				byte as char,
+		}
+	}
+
+	/// Encode a string, characters that cannot be represented become `?`.
+	pub fn encode(&self, string: &str) -> Vec<u8> {
+		if self.is_unicode() {
+			return string.as_bytes().to_vec();
+		}
+
+		string.chars().map(|ch| {
+			if (ch as u32) < 0x80 {
+				return ch as u8;
+			}
+
+			match *self {
+				Encoding::Utf8 =>
+					unreachable!(),
+
+				Encoding::Latin1 if (ch as u32) < 0x100 =>
+					ch as u8,
+
+				Encoding::Latin9 => {
+					if let Some(&(b, _)) = LATIN9.iter().find(|&&(_, c)| c == ch) {
+						b
+					}
+					else if (ch as u32) < 0x100 && !LATIN9.iter().any(|&(b, _)| b as u32 == ch as u32) {
+						ch as u8
+					}
+					else {
+						b'?'
+					}
+				}
+
+				Encoding::Cp437 =>
+					CP437.iter().position(|&c| c == ch).map(|i| i as u8 + 0x80).unwrap_or(b'?'),
+
+				_ =>
+					b'?'
+			}
+		}).collect()
+	}
+}
+
+/// Writer that turns 7-bit C1 controls into their 8-bit form when enabled.
+pub struct Controls<W: Write> {
+	inner:  W,
+	eight:  bool,
+	escape: bool,
+}
+
+impl<W: Write> Controls<W> {
+	pub fn new(inner: W, eight: bool) -> Self {
+		Controls {
+			inner:  inner,
+			eight:  eight,
+			escape: false,
+		}
+	}
+}
+
+impl<W: Write> Write for Controls<W> {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		if !self.eight {
+			return self.inner.write(buf);
+		}
+
+		let mut result = Vec::with_capacity(buf.len());
+
+		for &byte in buf {
+			if self.escape {
+				self.escape = false;
+
+				if byte >= 0x40 && byte < 0x60 {
+					result.push(byte + 0x40);
+				}
+				else {
+					result.push(0x1B);
+					result.push(byte);
+				}
+			}
+			else if byte == 0x1B {
+				self.escape = true;
+			}
+			else {
+				result.push(byte);
+			}
+		}
+
+		try!(self.inner.write_all(&result));
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		if self.escape {
+			self.escape = false;
+			try!(self.inner.write_all(b"\x1B"));
+		}
+
+		self.inner.flush()
+	}
+}
+
+impl<W: Write> Drop for Controls<W> {
+	fn drop(&mut self) {
+		if self.escape {
+			let _ = self.inner.write_all(b"\x1B");
+		}
+	}
+}
@@ -21,29 +21,45 @@ use std::collections::VecDeque;
 
 use itertools::Itertools;
 use util::clamp;
-use terminal::{Cell, Row, Free};
+use terminal::{Cell, Row, Free, Back};
 
 #[derive(Debug)]
 pub struct Grid {
-	cols:    u32,
-	rows:    u32,
-	history: usize,
+	cols:     u32,
+	rows:     u32,
+	history:  History,
+	scrolled: i64,
 
 	free: Free,
-	back: VecDeque<Row>,
+	back: Back,
 	view: VecDeque<Row>,
 }
 
+/// How much of the scroll back is kept.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum History {
+	/// Up to the given number of rows.
+	Rows(usize),
+
+	/// Up to the given approximate amount of memory in bytes.
+	Bytes(usize),
+
+	/// Everything.
+	Unlimited,
+}
+
 impl Grid {
-	/// Create a new grid.
-	pub fn new(cols: u32, rows: u32, history: usize) -> Self {
+	/// Create a new grid, optionally spilling the scroll back to disk up to the
+	/// given number of bytes.
+	pub fn new(cols: u32, rows: u32, history: History, spill: Option<usize>) -> Self {
 		let mut value = Grid {
-			cols:    0,
-			rows:    0,
-			history: history,
+			cols:     0,
+			rows:     0,
+			history:  history,
+			scrolled: 0,
 
 			free: Free::new(),
-			back: VecDeque::new(),
+			back: Back::new(spill),
 			view: VecDeque::new(),
 		};
 
@@ -52,7 +68,7 @@ impl Grid {
 	}
 
 	/// Get the scroll back.
-	pub fn back(&self) -> &VecDeque<Row> {
+	pub fn back(&self) -> &Back {
 		&self.back
 	}
 
@@ -61,17 +77,49 @@ impl Grid {
 		&self.view
 	}
 
+	/// Get how many rows have been scrolled into the scroll back, the absolute
+	/// line of a row in the view is this plus its Y.
+	pub fn scrolled(&self) -> i64 {
+		self.scrolled
+	}
+
+	/// Get the absolute line of the oldest row in the scroll back.
+	pub fn oldest(&self) -> i64 {
+		self.scrolled - self.back.len() as i64
+	}
+
+	/// Get the approximate memory used by the scroll back.
+	pub fn size(&self) -> usize {
+		self.back.size()
+	}
+
 	/// Drop rows in the scrollback that go beyond the history limit.
 	pub fn clean_history(&mut self) {
-		if self.back.len() > self.history {
-			let overflow = self.back.len() - self.history;
+		self.back.limit(self.history);
+	}
 
-			for row in self.back.drain(.. overflow) {
-				self.free.push(row);
-			}
+	/// Drop the whole scroll back.
+	pub fn clear_history(&mut self) {
+		self.back.clear();
+	}
+
+	/// Inflate the scroll back rows between the lines `start` and `end`
+	/// inclusive so they can be borrowed, lines are numbered like in captures.
+	pub fn inflate(&mut self, start: i64, end: i64) {
+		let length = self.back.len() as i64;
+		let start  = length.saturating_add(start).max(0);
+		let end    = length.saturating_add(end).saturating_add(1).min(length);
+
+		if start < end {
+			self.back.inflate(start as usize, end as usize);
 		}
 	}
 
+	/// Drop the scroll back rows inflated for reading.
+	pub fn deflate(&mut self) {
+		self.back.deflate();
+	}
+
 	/// Clean left-over references from changes.
 	pub fn clean_references(&mut self, x: u32, y: u32) {
 		if !self.view[y as usize][x as usize].is_reference() {
@@ -104,6 +152,20 @@ impl Grid {
 				else if !wrapped.is_empty() {
 					wrapped.push(view.remove(i).unwrap());
 
+					// Rows with image fragments are never reflowed, or the picture
+					// would be scattered, they are just padded to the new width.
+					if wrapped.iter().any(|r| r.iter().any(Cell::is_image)) {
+						for mut row in mem::replace(&mut wrapped, Vec::new()) {
+							if row.len() < cols as usize {
+								row.resize(cols as usize, free.cell());
+							}
+
+							view.insert(i, row);
+						}
+
+						continue;
+					}
+
 					let mut unwrapped = Vec::new();
 					let     before    = wrapped.len();
 
@@ -154,7 +216,9 @@ impl Grid {
 						row.pop_back();
 					}
 
-					if row.len() != cols as usize {
+					// Keep image fragments together, the overflowing part is clipped
+					// and comes back when the grid gets wide enough again.
+					if row.len() != cols as usize && !row.iter().any(Cell::is_image) {
 						let mut wrapped = Vec::new();
 						let     chunks  = row.inner.into_iter().chunks(cols as usize);
 
@@ -200,7 +264,11 @@ impl Grid {
 		self.rows = rows;
 
 		let mut offset = resize(&mut self.view, &mut self.free, cols);
-		resize(&mut self.back, &mut self.free, cols);
+
+		{
+			let free = &mut self.free;
+			self.back.reflow(cols, |rows| { resize(rows, free, cols); });
+		}
 
 		if self.view.len() > rows as usize {
 			while self.view.len() > rows as usize && self.view.back().unwrap().iter().all(|v| v.is_empty()) {
@@ -208,15 +276,22 @@ impl Grid {
 			}
 
 			let overflow = self.view.len() - rows as usize;
-			self.back.extend(self.view.drain(.. overflow));
+			for row in self.view.drain(.. overflow) {
+				if let Some(row) = self.back.push(row) {
+					self.free.push(row);
+				}
+			}
+
+			self.scrolled += overflow as i64;
 		}
 
 		if self.view.len() < rows as usize {
 			let overflow = rows as usize - self.view.len();
 
 			for _ in 0 .. overflow {
-				if let Some(row) = self.back.pop_back() {
-					offset += 1;
+				if let Some(row) = self.back.pop() {
+					offset        += 1;
+					self.scrolled -= 1;
 					self.view.push_front(row);
 				}
 				else {
@@ -276,8 +351,13 @@ impl Grid {
 			}
 		}
 		else {
+			// The row is compacted into the scroll back, so it can be reused.
+			if let Some(row) = self.back.push(self.view.pop_front().unwrap()) {
+				self.free.push(row);
+			}
+
 			self.view.push_back(self.free.pop(self.cols as usize));
-			self.back.push_back(self.view.pop_front().unwrap());
+			self.scrolled += 1;
 		}
 
 		self.clean_history();
@@ -0,0 +1,357 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::rc::Rc;
+use std::cmp;
+use std::collections::HashMap;
+use std::hash::BuildHasherDefault;
+use fnv::FnvHasher;
+
+use picto;
+use sys::cairo;
+
+/// The largest width or height of an image, in pixels.
+pub const MAXIMUM: u32 = 32767;
+
+/// The most memory used by stored images, past it the least recently used
+/// images without placements are dropped.
+const BUDGET: u64 = 320 * 1024 * 1024;
+
+/// Storage for images and their placements.
+#[derive(Default, Debug)]
+pub struct Images {
+	next:       u32,
+	inner:      HashMap<u32, Stored, BuildHasherDefault<FnvHasher>>,
+	numbers:    HashMap<u32, u32, BuildHasherDefault<FnvHasher>>,
+	placements: Vec<Placement>,
+
+	size: u64,
+	used: u64,
+}
+
+#[derive(Debug)]
+struct Stored {
+	image: Rc<cairo::Image>,
+	used:  u64,
+}
+
+/// A placement of an image within the grid.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Placement {
+	/// The image being placed.
+	pub image: u32,
+
+	/// The placement identifier, 0 if not set.
+	pub id: u32,
+
+	/// The column of the top left corner.
+	pub x: u32,
+
+	/// The absolute line of the top left corner.
+	pub line: i64,
+
+	/// How many cells the placement covers.
+	pub columns: u32,
+	pub rows:    u32,
+
+	/// The pixel offset within the top left cell.
+	pub offset: (u32, u32),
+
+	/// The rectangle within the image to display.
+	pub source: (u32, u32, u32, u32),
+
+	/// The size in pixels the source rectangle is drawn at.
+	pub size: (u32, u32),
+
+	/// The stacking order, negative values are drawn below text.
+	pub z: i32,
+}
+
+/// A placement covering a cell on screen.
+#[derive(Clone, Debug)]
+pub struct Placed {
+	pub image:     Rc<cairo::Image>,
+	pub placement: Placement,
+
+	/// The on screen cell of the top left corner, it can be outside the screen.
+	pub origin: (i64, i64),
+}
+
+impl Placement {
+	/// Check if the placement covers the given cell.
+	pub fn contains(&self, x: u32, line: i64) -> bool {
+		x >= self.x && x < self.x + self.columns &&
+		line >= self.line && line < self.line + self.rows as i64
+	}
+}
+
+impl Images {
+	/// Store an image, returning its identifier.
+	pub fn insert(&mut self, id: Option<u32>, number: Option<u32>, image: cairo::Image) -> u32 {
+		let id = if let Some(id) = id {
+			id
+		}
+		else {
+			// Pick an identifier from the top of the range, so it doesn't clash
+			// with ones picked by clients.
+			loop {
+				self.next = self.next.wrapping_add(1);
+				let id = u32::max_value() - self.next;
+
+				if !self.inner.contains_key(&id) {
+					break id;
+				}
+			}
+		};
+
+		if let Some(number) = number {
+			self.numbers.insert(number, id);
+		}
+
+		self.used += 1;
+		self.size += size(&image);
+
+		let stored = Stored { image: Rc::new(image), used: self.used };
+		if let Some(previous) = self.inner.insert(id, stored) {
+			self.size -= size(&previous.image);
+		}
+
+		self.evict(id);
+		id
+	}
+
+	/// Get the image with the given identifier.
+	pub fn get(&self, id: u32) -> Option<&Rc<cairo::Image>> {
+		self.inner.get(&id).map(|s| &s.image)
+	}
+
+	/// Find the most recent image with the given number.
+	pub fn find(&self, number: u32) -> Option<u32> {
+		self.numbers.get(&number).cloned()
+	}
+
+	/// Get all the placements.
+	pub fn placements(&self) -> &[Placement] {
+		&self.placements
+	}
+
+	/// Check if there are any placements.
+	pub fn is_empty(&self) -> bool {
+		self.placements.is_empty()
+	}
+
+	/// Add a placement, replacing any with the same identifier.
+	pub fn place(&mut self, placement: Placement) -> Option<Placement> {
+		self.used += 1;
+		if let Some(stored) = self.inner.get_mut(&placement.image) {
+			stored.used = self.used;
+		}
+
+		let previous = if placement.id != 0 {
+			self.placements.iter()
+				.position(|p| p.image == placement.image && p.id == placement.id)
+				.map(|i| self.placements.remove(i))
+		}
+		else {
+			None
+		};
+
+		self.placements.push(placement);
+		self.placements.sort_by_key(|p| p.z);
+
+		previous
+	}
+
+	/// Remove the placements not matching the predicate, optionally freeing the
+	/// images left without placements, and return the removed ones.
+	pub fn retain<F: FnMut(&Placement) -> bool>(&mut self, mut f: F, free: bool) -> Vec<Placement> {
+		let mut removed = Vec::new();
+
+		for placement in ::std::mem::replace(&mut self.placements, Vec::new()) {
+			if f(&placement) {
+				self.placements.push(placement);
+			}
+			else {
+				removed.push(placement);
+			}
+		}
+
+		if free {
+			for placement in &removed {
+				if !self.placements.iter().any(|p| p.image == placement.image) {
+					self.remove(placement.image);
+				}
+			}
+		}
+
+		removed
+	}
+
+	/// Remove an image and its placements.
+	pub fn remove(&mut self, id: u32) -> Vec<Placement> {
+		if let Some(stored) = self.inner.remove(&id) {
+			self.size -= size(&stored.image);
+		}
+
+		self.numbers.retain(|_, v| *v != id);
+		self.retain(|p| p.image != id, false)
+	}
+
+	/// Drop the least recently used images without placements until the
+	/// storage is within budget, the given image is kept.
+	fn evict(&mut self, keep: u32) {
+		while self.size > BUDGET {
+			let unused = {
+				let placements = &self.placements;
+
+				self.inner.iter()
+					.filter(|&(&id, _)| id != keep && !placements.iter().any(|p| p.image == id))
+					.min_by_key(|&(_, stored)| stored.used)
+					.map(|(&id, _)| id)
+			};
+
+			if let Some(id) = unused {
+				self.remove(id);
+			}
+			else {
+				break;
+			}
+		}
+	}
+
+	/// Drop placements that scrolled out of the history.
+	pub fn clean(&mut self, oldest: i64) -> Vec<Placement> {
+		self.retain(|p| p.line + p.rows as i64 > oldest, false)
+	}
+
+	/// Get the placements covering the given on screen cell, ordered by
+	/// stacking order.
+	pub fn at(&self, x: u32, y: u32, line: i64) -> Vec<Placed> {
+		self.placements.iter().filter(|p| p.contains(x, line)).filter_map(|p|
+			self.inner.get(&p.image).map(|stored| Placed {
+				image:     stored.image.clone(),
+				placement: p.clone(),
+				origin:    (p.x as i64, y as i64 - (line - p.line)),
+			})).collect()
+	}
+}
+
+/// Decode an encoded image into its size and non-premultiplied RGBA pixels.
+pub fn decode(data: &[u8]) -> Option<(u32, u32, Vec<u8>)> {
+	use picto::color::Rgba;
+
+	let buffer = try!(option picto::read::from_memory::<Rgba, u8, _>(data).ok());
+	Some((buffer.width(), buffer.height(), buffer.as_ref().to_vec()))
+}
+
+/// Decode a PNG into an image, the size is checked from the header before
+/// anything is decoded.
+pub fn png(data: &[u8]) -> Option<cairo::Image> {
+	let (width, height) = try!(option header(data));
+
+	if width == 0 || height == 0 || width > MAXIMUM || height > MAXIMUM {
+		return None;
+	}
+
+	let (width, height, pixels) = try!(option decode(data));
+	Some(cairo::Image::from_rgba(width, height, &pixels))
+}
+
+/// Read the size of a PNG from its header.
+fn header(data: &[u8]) -> Option<(u32, u32)> {
+	fn u32(value: &[u8]) -> u32 {
+		(value[0] as u32) << 24 | (value[1] as u32) << 16 | (value[2] as u32) << 8 | value[3] as u32
+	}
+
+	if data.len() < 24 || !data.starts_with(b"\x89PNG\r\n\x1A\n") || &data[12 .. 16] != b"IHDR" {
+		return None;
+	}
+
+	Some((u32(&data[16 .. 20]), u32(&data[20 .. 24])))
+}
+
+/// Get the memory used by an image.
+fn size(image: &cairo::Image) -> u64 {
+	image.width() as u64 * image.height() as u64 * 4
+}
+
+/// Scale RGBA pixels to the given size and slice them into cell sized images,
+/// row by row, limited to the given number of columns.
+pub fn slice(data: &[u8], (width, height): (u32, u32), size: (u32, u32), cell: (u32, u32), limit: u32) -> Vec<Vec<cairo::Image>> {
+	let columns = cmp::min(limit, (size.0 + cell.0 - 1) / cell.0);
+	let rows    = (size.1 + cell.1 - 1) / cell.1;
+	let mut grid = Vec::with_capacity(rows as usize);
+
+	for row in 0 .. rows {
+		let mut images = Vec::with_capacity(columns as usize);
+
+		for column in 0 .. columns {
+			let mut image = cairo::Image::new(cell.0, cell.1);
+
+			for y in 0 .. cell.1 {
+				for x in 0 .. cell.0 {
+					let (px, py) = (column * cell.0 + x, row * cell.1 + y);
+
+					if px >= size.0 || py >= size.1 {
+						continue;
+					}
+
+					// Nearest neighbour is good enough for terminal images.
+					let offset = (((py as u64 * height as u64 / size.1 as u64) * width as u64 +
+						(px as u64 * width as u64 / size.0 as u64)) * 4) as usize;
+					let a = data[offset + 3] as u32;
+
+					image.set(x, y, &(
+						(data[offset + 0] as u32 * a / 255) as u8,
+						(data[offset + 1] as u32 * a / 255) as u8,
+						(data[offset + 2] as u32 * a / 255) as u8,
+						a as u8));
+				}
+			}
+
+			images.push(image);
+		}
+
+		grid.push(images);
+	}
+
+	grid
+}
+
+/// Turn raw RGB or RGBA pixels into an image.
+pub fn raw(data: &[u8], width: u32, height: u32, alpha: bool) -> Option<cairo::Image> {
+	let depth  = if alpha { 4 } else { 3 };
+	let pixels = try!(option (width as u64).checked_mul(height as u64));
+
+	if width == 0 || height == 0 || width > MAXIMUM || height > MAXIMUM || (data.len() as u64) < pixels * depth {
+		return None;
+	}
+
+	if alpha {
+		Some(cairo::Image::from_rgba(width, height, data))
+	}
+	else {
+		let mut rgba = Vec::with_capacity((pixels * 4) as usize);
+
+		for px in data[.. (pixels * 3) as usize].chunks(3) {
+			rgba.extend_from_slice(px);
+			rgba.push(255);
+		}
+
+		Some(cairo::Image::from_rgba(width, height, &rgba))
+	}
+}
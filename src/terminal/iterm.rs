@@ -0,0 +1,125 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::cmp;
+
+use base64;
+
+/// An iTerm2 inline file transfer.
+#[derive(Clone, Debug)]
+pub struct File {
+	pub name:   Option<String>,
+	pub inline: bool,
+
+	pub width:    Dimension,
+	pub height:   Dimension,
+	pub preserve: bool,
+
+	pub data: Vec<u8>,
+}
+
+/// A requested dimension of the image.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Dimension {
+	Auto,
+	Cells(u32),
+	Pixels(u32),
+	Percent(u32),
+}
+
+impl Dimension {
+	/// Parse a dimension argument.
+	pub fn parse(value: &str) -> Option<Self> {
+		if value == "auto" {
+			Some(Dimension::Auto)
+		}
+		else if value.ends_with("px") {
+			value[.. value.len() - 2].parse().ok().map(Dimension::Pixels)
+		}
+		else if value.ends_with('%') {
+			value[.. value.len() - 1].parse().ok().map(Dimension::Percent)
+		}
+		else {
+			value.parse().ok().map(Dimension::Cells)
+		}
+	}
+
+	/// Get the size in pixels, given the cell size and the number of cells
+	/// available, `None` means it should be computed automatically.
+	///
+	/// Sizes too big to be represented saturate.
+	pub fn pixels(&self, cell: u32, cells: u32) -> Option<u32> {
+		let size = match *self {
+			Dimension::Auto       => return None,
+			Dimension::Cells(n)   => (n as u64).checked_mul(cell as u64),
+			Dimension::Pixels(n)  => Some(n as u64),
+			Dimension::Percent(n) => (cells as u64 * cell as u64).checked_mul(n as u64).map(|v| v / 100),
+		};
+
+		Some(size.map(|v| cmp::min(v, u32::max_value() as u64) as u32).unwrap_or(u32::max_value()))
+	}
+}
+
+/// Parse the content of an OSC 1337 string, without the leading `File=`.
+pub fn parse(string: &str) -> Option<File> {
+	let mut file = File {
+		name:   None,
+		inline: false,
+
+		width:    Dimension::Auto,
+		height:   Dimension::Auto,
+		preserve: true,
+
+		data: Vec::new(),
+	};
+
+	let mut parts = string.splitn(2, ':');
+	let     args  = try!(option parts.next());
+	let     data  = try!(option parts.next());
+
+	for pair in args.split(';').filter(|p| !p.is_empty()) {
+		let mut pair  = pair.splitn(2, '=');
+		let     key   = try!(option pair.next());
+		let     value = try!(option pair.next());
+
+		match key {
+			"name" =>
+				file.name = base64::decode(value).ok().map(|v| String::from_utf8_lossy(&v).into_owned()),
+
+			"inline" =>
+				file.inline = value == "1",
+
+			"width" =>
+				file.width = try!(option Dimension::parse(value)),
+
+			"height" =>
+				file.height = try!(option Dimension::parse(value)),
+
+			"preserveAspectRatio" =>
+				file.preserve = value != "0",
+
+			_ =>
+				debug!(target: "cancer::terminal::iterm", "unknown argument: {}={}", key, value),
+		}
+	}
+
+	// Some tools wrap the payload, so drop any whitespace.
+	let data = data.chars().filter(|c| !c.is_whitespace()).collect::<String>();
+	file.data = try!(option base64::decode(&data).ok());
+
+	Some(file)
+}
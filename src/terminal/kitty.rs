@@ -0,0 +1,302 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::os::unix::io::FromRawFd;
+use std::ffi::CString;
+use std::env;
+use std::cmp;
+use std::path::Path;
+
+use libc;
+use base64;
+use flate2::read::ZlibDecoder;
+use sys::cairo;
+use terminal::image;
+
+/// The most data read from a file, shared memory or decompressed.
+const LIMIT: u64 = 256 * 1024 * 1024;
+
+/// A kitty graphics protocol request.
+#[derive(Clone, Debug)]
+pub struct Request {
+	pub action: u8,
+	pub quiet:  u8,
+
+	pub format:      u32,
+	pub medium:      u8,
+	pub compression: Option<u8>,
+	pub more:        bool,
+
+	pub id:        Option<u32>,
+	pub number:    Option<u32>,
+	pub placement: Option<u32>,
+
+	pub width:  u32,
+	pub height: u32,
+	pub size:   Option<u64>,
+	pub skip:   u64,
+
+	pub source:  (u32, u32, u32, u32),
+	pub columns: u32,
+	pub rows:    u32,
+	pub offset:  (u32, u32),
+	pub z:       i32,
+	pub cursor:  bool,
+
+	pub delete: u8,
+
+	pub payload: Vec<u8>,
+}
+
+/// An error to be reported back.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Error {
+	Invalid(&'static str),
+	Missing(&'static str),
+	NoData(&'static str),
+	Unsupported(&'static str),
+}
+
+impl Error {
+	/// Get the error as it's sent back.
+	pub fn message(&self) -> String {
+		match *self {
+			Error::Invalid(msg)     => format!("EINVAL:{}", msg),
+			Error::Missing(msg)     => format!("ENOENT:{}", msg),
+			Error::NoData(msg)      => format!("ENODATA:{}", msg),
+			Error::Unsupported(msg) => format!("ENOTSUP:{}", msg),
+		}
+	}
+}
+
+impl Default for Request {
+	fn default() -> Self {
+		Request {
+			action: b't',
+			quiet:  0,
+
+			format:      32,
+			medium:      b'd',
+			compression: None,
+			more:        false,
+
+			id:        None,
+			number:    None,
+			placement: None,
+
+			width:  0,
+			height: 0,
+			size:   None,
+			skip:   0,
+
+			source:  (0, 0, 0, 0),
+			columns: 0,
+			rows:    0,
+			offset:  (0, 0),
+			z:       0,
+			cursor:  true,
+
+			delete: b'a',
+
+			payload: Vec::new(),
+		}
+	}
+}
+
+/// Parse the content of an APC string, without the leading `G`.
+pub fn parse(string: &str) -> Option<Request> {
+	let mut request = Request::default();
+	let mut parts   = string.splitn(2, ';');
+
+	for pair in parts.next().unwrap_or("").split(',').filter(|p| !p.is_empty()) {
+		let mut pair  = pair.splitn(2, '=');
+		let     key   = try!(option pair.next());
+		let     value = try!(option pair.next());
+
+		macro_rules! number {
+			() => (try!(option value.parse().ok()))
+		}
+
+		macro_rules! byte {
+			() => (try!(option value.bytes().next()))
+		}
+
+		match key {
+			"a" => request.action      = byte!(),
+			"q" => request.quiet       = number!(),
+			"f" => request.format      = number!(),
+			"t" => request.medium      = byte!(),
+			"o" => request.compression = Some(byte!()),
+			"m" => request.more        = value == "1",
+			"i" => request.id          = Some(number!()),
+			"I" => request.number      = Some(number!()),
+			"p" => request.placement   = Some(number!()),
+			"s" => request.width       = number!(),
+			"v" => request.height      = number!(),
+			"S" => request.size        = Some(number!()),
+			"O" => request.skip        = number!(),
+			"x" => request.source.0    = number!(),
+			"y" => request.source.1    = number!(),
+			"w" => request.source.2    = number!(),
+			"h" => request.source.3    = number!(),
+			"c" => request.columns     = number!(),
+			"r" => request.rows        = number!(),
+			"X" => request.offset.0    = number!(),
+			"Y" => request.offset.1    = number!(),
+			"z" => request.z           = number!(),
+			"C" => request.cursor      = value != "1",
+			"d" => request.delete      = byte!(),
+			_   => debug!(target: "cancer::terminal::kitty", "unknown key: {}={}", key, value),
+		}
+	}
+
+	if let Some(payload) = parts.next() {
+		request.payload = try!(option base64::decode(payload.trim()).ok());
+	}
+
+	Some(request)
+}
+
+impl Request {
+	/// Append the payload of a following chunk, failing once the collected
+	/// payload goes beyond the limit.
+	pub fn chunk(&mut self, next: Request) -> Result<(), Error> {
+		if (self.payload.len() + next.payload.len()) as u64 > LIMIT {
+			return Err(Error::Invalid("too much data"));
+		}
+
+		self.payload.extend(next.payload);
+		self.more = next.more;
+
+		Ok(())
+	}
+
+	/// Load the image data from the transmission medium, and decode it.
+	pub fn load(&self) -> Result<cairo::Image, Error> {
+		let data = match self.medium {
+			b'd' =>
+				self.payload.clone(),
+
+			b'f' | b't' => {
+				let path = String::from_utf8_lossy(&self.payload).into_owned();
+
+				// Temporary files can only be deleted if they are actually in a
+				// temporary directory and look like they were made for us.
+				if self.medium == b't' && (!Path::new(&path).starts_with(env::temp_dir()) ||
+				                           !path.contains("tty-graphics-protocol"))
+				{
+					return Err(Error::Invalid("not a temporary file"));
+				}
+
+				let data = self.read(file(&path));
+
+				if self.medium == b't' {
+					let _ = fs::remove_file(&path);
+				}
+
+				try!(data)
+			}
+
+			b's' => {
+				let name = try!(CString::new(self.payload.clone()).map_err(|_| Error::Invalid("bad name")));
+
+				unsafe {
+					let fd = libc::shm_open(name.as_ptr(), libc::O_RDONLY, 0);
+
+					if fd < 0 {
+						return Err(Error::Missing("cannot open shared memory"));
+					}
+
+					let data = self.read(Ok(File::from_raw_fd(fd)));
+					libc::shm_unlink(name.as_ptr());
+
+					try!(data)
+				}
+			}
+
+			_ =>
+				return Err(Error::Unsupported("unknown medium"))
+		};
+
+		let data = match self.compression {
+			None =>
+				data,
+
+			Some(b'z') => {
+				let mut result = Vec::new();
+				try!(ZlibDecoder::new(&data[..]).take(LIMIT + 1).read_to_end(&mut result).map_err(|_| Error::Invalid("bad compression")));
+
+				if result.len() as u64 > LIMIT {
+					return Err(Error::Invalid("too much data"));
+				}
+
+				result
+			}
+
+			Some(_) =>
+				return Err(Error::Unsupported("unknown compression"))
+		};
+
+		let image = match self.format {
+			24 => image::raw(&data, self.width, self.height, false),
+			32 => image::raw(&data, self.width, self.height, true),
+			100 => image::png(&data),
+			_ => return Err(Error::Unsupported("unknown format")),
+		};
+
+		image.ok_or(Error::NoData("cannot decode image"))
+	}
+
+	fn read(&self, file: Result<File, Error>) -> Result<Vec<u8>, Error> {
+		let mut file = try!(file);
+		let mut data = Vec::new();
+
+		if self.skip > 0 {
+			try!(file.seek(SeekFrom::Start(self.skip)).map_err(|_| Error::Invalid("cannot seek")));
+		}
+
+		// Never read more than the limit, whatever the size says.
+		let size = cmp::min(self.size.unwrap_or(LIMIT + 1), LIMIT + 1);
+		try!(file.take(size).read_to_end(&mut data).map_err(|_| Error::NoData("cannot read")));
+
+		if data.len() as u64 > LIMIT {
+			return Err(Error::Invalid("too much data"));
+		}
+
+		Ok(data)
+	}
+}
+
+/// Open a file to read image data from, only regular files outside of the
+/// kernel and device file systems are accepted.
+fn file(path: &str) -> Result<File, Error> {
+	let real = try!(fs::canonicalize(path).map_err(|_| Error::Missing("cannot open file")));
+
+	if real.starts_with("/proc") || real.starts_with("/sys") || real.starts_with("/dev") {
+		return Err(Error::Invalid("not a regular file"));
+	}
+
+	let file = try!(File::open(&real).map_err(|_| Error::Missing("cannot open file")));
+
+	if !try!(file.metadata().map_err(|_| Error::Missing("cannot open file"))).is_file() {
+		return Err(Error::Invalid("not a regular file"));
+	}
+
+	Ok(file)
+}
@@ -0,0 +1,128 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::io::Write;
+use std::fs::{File, OpenOptions};
+use std::os::unix::fs::OpenOptionsExt;
+use std::ffi::CStr;
+use std::mem;
+use std::ptr;
+
+use libc;
+use config::environment::{self, Logging};
+use terminal::printer::expand;
+
+/// The session log.
+#[derive(Debug)]
+pub struct Logger {
+	config: environment::Log,
+	file:   Option<File>,
+}
+
+impl Logger {
+	/// Create the log, it's opened right away if enabled.
+	pub fn new(config: &environment::Log) -> Self {
+		let mut log = Logger {
+			config: config.clone(),
+			file:   None,
+		};
+
+		if config.enabled() {
+			log.open();
+		}
+
+		log
+	}
+
+	/// Check if logging is on.
+	pub fn is_enabled(&self) -> bool {
+		self.file.is_some()
+	}
+
+	/// Turn logging on or off, returns whether it's on.
+	pub fn toggle(&mut self) -> bool {
+		if self.file.take().is_none() {
+			self.open();
+		}
+
+		self.is_enabled()
+	}
+
+	/// Log the raw tty output.
+	pub fn raw(&mut self, data: &[u8]) {
+		if self.config.mode() == Logging::Raw {
+			self.write(data);
+		}
+	}
+
+	/// Log a completed line.
+	pub fn line(&mut self, text: &str) {
+		match self.config.mode() {
+			Logging::Raw =>
+				(),
+
+			Logging::Plain =>
+				self.write(text.as_bytes()),
+
+			Logging::Timestamped => {
+				let line = format!("{} {}", now(), text);
+				self.write(line.as_bytes());
+			}
+		}
+	}
+
+	fn open(&mut self) {
+		let path = expand(self.config.path());
+
+		// The log can contain anything that went through the terminal.
+		match OpenOptions::new().create(true).append(true).mode(0o600).open(&path) {
+			Ok(file) =>
+				self.file = Some(file),
+
+			Err(err) =>
+				error!(target: "cancer::terminal::logger", "could not open {}: {}", path, err),
+		}
+	}
+
+	fn write(&mut self, data: &[u8]) {
+		let result = if let Some(file) = self.file.as_mut() {
+			file.write_all(data)
+		}
+		else {
+			return;
+		};
+
+		if let Err(err) = result {
+			error!(target: "cancer::terminal::logger", "could not log: {}", err);
+			self.file = None;
+		}
+	}
+}
+
+/// The local time for timestamps.
+fn now() -> String {
+	unsafe {
+		let mut buffer = [0 as libc::c_char; 32];
+		let mut tm     = mem::zeroed::<libc::tm>();
+		let     time   = libc::time(ptr::null_mut());
+
+		libc::localtime_r(&time, &mut tm);
+		libc::strftime(buffer.as_mut_ptr(), buffer.len(), b"%Y-%m-%d %H:%M:%S\0".as_ptr() as *const _, &tm);
+
+		CStr::from_ptr(buffer.as_ptr()).to_string_lossy().into_owned()
+	}
+}
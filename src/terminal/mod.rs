@@ -27,6 +27,11 @@ pub use self::row::Row;
 mod free;
 pub use self::free::Free;
 
+mod back;
+pub use self::back::Back;
+
+mod spill;
+
 pub mod touched;
 pub use self::touched::Touched;
 
@@ -42,11 +47,48 @@ pub use self::grid::Grid;
 mod tabs;
 pub use self::tabs::Tabs;
 
+pub mod charset;
+
+pub mod encoding;
+pub use self::encoding::Encoding;
+
 mod input;
 pub use self::input::Input;
 
-mod sixel;
+#[cfg(feature = "images")]
+pub mod sixel;
+#[cfg(feature = "images")]
 pub use self::sixel::Sixel;
 
+#[cfg(feature = "images")]
+pub mod image;
+#[cfg(feature = "images")]
+pub use self::image::Images;
+
+#[cfg(feature = "images")]
+mod kitty;
+#[cfg(feature = "images")]
+mod iterm;
+
+#[cfg(feature = "images")]
+pub mod regis;
+#[cfg(feature = "images")]
+pub use self::regis::Regis;
+
+pub mod soft;
+pub use self::soft::Soft;
+
+pub mod status;
+
+pub mod bidi;
+
+mod printer;
+pub use self::printer::Printer;
+
+mod logger;
+pub use self::logger::Logger;
+
+pub mod capture;
+
 mod terminal;
 pub use self::terminal::Terminal;
@@ -29,6 +29,16 @@ bitflags! {
 		const ECHO               = 1 << 9;
 		const FOCUS              = 1 << 10;
 		const UTF8               = 1 << 11;
+		const EIGHT_BIT          = 1 << 17;
+		const SIXEL_DISPLAY      = 1 << 18;
+		const SIXEL_PRIVATE      = 1 << 19;
+		const SIXEL_CURSOR       = 1 << 20;
+		const REVERSE_WRAP       = 1 << 21;
+		const EXTENDED_WRAP      = 1 << 22;
+		const COLUMNS            = 1 << 23;
+		const BIDI               = 1 << 24;
+		const RIGHT_TO_LEFT      = 1 << 25;
+		const AUTO_PRINT         = 1 << 26;
 
 		const MOUSE_BUTTON = 1 << 12;
 		const MOUSE_MOTION = 1 << 13;
@@ -44,6 +54,6 @@ bitflags! {
 
 impl Default for Mode {
 	fn default() -> Self {
-		WRAP | UTF8
+		WRAP | UTF8 | SIXEL_PRIVATE | BIDI
 	}
 }
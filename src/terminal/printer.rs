@@ -0,0 +1,223 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+use std::io::Write;
+use std::fs::OpenOptions;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{SyncSender, TrySendError, sync_channel};
+use std::env;
+use std::thread;
+
+use config::environment;
+
+/// The sequences ending printer controller mode.
+const END: &[u8]       = b"\x1B[4i";
+const END_EIGHT: &[u8] = b"\x9B4i";
+
+/// How many writes can be queued before a printer falling behind starts
+/// losing data.
+const QUEUE: usize = 1024;
+
+/// A media copy target, either a command or a file.
+#[derive(Debug)]
+pub struct Printer {
+	target: Option<environment::Printer>,
+	job:    Option<Job>,
+	auto:   Option<Job>,
+}
+
+/// A print job, the data is written out by its own thread so a slow or stuck
+/// printer never holds up the terminal.
+#[derive(Debug)]
+enum Job {
+	Writer(SyncSender<Vec<u8>>),
+	Discard,
+}
+
+impl Printer {
+	/// Create a printer for the configured target.
+	pub fn new(target: Option<&environment::Printer>) -> Self {
+		Printer {
+			target: target.cloned(),
+			job:    None,
+			auto:   None,
+		}
+	}
+
+	/// Check if printer controller mode is on.
+	pub fn is_controlling(&self) -> bool {
+		self.job.is_some()
+	}
+
+	/// Print the data, either in the current job or in a job of its own.
+	pub fn print(&mut self, data: &[u8]) {
+		if self.job.is_some() {
+			self.write(data);
+		}
+		else if self.auto.is_some() {
+			send(&mut self.auto, data);
+		}
+		else {
+			let mut job = Some(self.spawn());
+			send(&mut job, data);
+		}
+	}
+
+	/// Start a print job, the data will be sent to it until stopped.
+	pub fn start(&mut self) {
+		if self.job.is_none() {
+			self.job = Some(self.spawn());
+		}
+	}
+
+	/// Send data to the current print job.
+	pub fn write(&mut self, data: &[u8]) {
+		send(&mut self.job, data);
+	}
+
+	/// Stop the current print job.
+	pub fn stop(&mut self) {
+		self.job.take();
+	}
+
+	/// Enable or disable auto print, the printed lines all go in one job that
+	/// is kept open while enabled.
+	pub fn auto(&mut self, value: bool) {
+		if !value {
+			self.auto.take();
+		}
+		else if self.auto.is_none() {
+			self.auto = Some(self.spawn());
+		}
+	}
+
+	/// Spawn a job for the configured target.
+	fn spawn(&self) -> Job {
+		let job = match self.target {
+			Some(environment::Printer::Command(ref command)) =>
+				Command::new("sh").arg("-c").arg(command)
+					.stdin(Stdio::piped()).stdout(Stdio::null())
+					.spawn().map(|mut child| {
+						let input = child.stdin.take().unwrap();
+
+						// Closing the input lets the command finish, then reap it.
+						writer(input, move || { let _ = child.wait(); })
+					}),
+
+			Some(environment::Printer::File(ref path)) =>
+				OpenOptions::new().create(true).append(true).open(expand(path))
+					.map(|file| writer(file, || ())),
+
+			None =>
+				Ok(Job::Discard),
+		};
+
+		// Without a printer the job is still started, the data is dropped.
+		match job {
+			Ok(job) =>
+				job,
+
+			Err(err) => {
+				error!(target: "cancer::terminal::printer", "could not start print job: {}", err);
+				Job::Discard
+			}
+		}
+	}
+}
+
+/// Spawn the thread writing the data sent to the job, `done` is called once
+/// the job is over and the output closed.
+fn writer<W, F>(output: W, done: F) -> Job
+	where W: Write + Send + 'static,
+	      F: FnOnce() + Send + 'static
+{
+	let (sender, receiver) = sync_channel::<Vec<u8>>(QUEUE);
+
+	thread::spawn(move || {
+		let mut output = output;
+
+		for data in receiver {
+			if let Err(err) = output.write_all(&data) {
+				error!(target: "cancer::terminal::printer", "could not print: {}", err);
+				break;
+			}
+		}
+
+		let _ = output.flush();
+		drop(output);
+		done();
+	});
+
+	Job::Writer(sender)
+}
+
+/// Queue data on the job without blocking, a job whose writer is gone turns
+/// into a discarding one.
+fn send(job: &mut Option<Job>, data: &[u8]) {
+	let gone = if let Some(Job::Writer(ref sender)) = *job {
+		match sender.try_send(data.to_vec()) {
+			Ok(()) =>
+				false,
+
+			Err(TrySendError::Full(_)) => {
+				error!(target: "cancer::terminal::printer", "printer is falling behind, dropping data");
+				false
+			}
+
+			Err(TrySendError::Disconnected(_)) =>
+				true,
+		}
+	}
+	else {
+		false
+	};
+
+	if gone {
+		*job = Some(Job::Discard);
+	}
+}
+
+/// Find the end of printer controller mode, returns the data to print, the
+/// rest of the input and whether the end was found.
+pub fn split(input: &[u8], eight: bool) -> (&[u8], &[u8], bool) {
+	for i in 0 .. input.len() {
+		if input[i ..].starts_with(END) {
+			return (&input[.. i], &input[i + END.len() ..], true);
+		}
+
+		if eight && input[i ..].starts_with(END_EIGHT) {
+			return (&input[.. i], &input[i + END_EIGHT.len() ..], true);
+		}
+
+		// Keep back a possibly incomplete end sequence.
+		if END.starts_with(&input[i ..]) || (eight && END_EIGHT.starts_with(&input[i ..])) {
+			return (&input[.. i], &input[i ..], false);
+		}
+	}
+
+	(input, &[], false)
+}
+
+/// Expand the home directory in a path.
+pub fn expand(path: &str) -> String {
+	if path.starts_with("~/") {
+		if let Some(home) = env::var_os("HOME") {
+			return format!("{}{}", home.to_string_lossy(), &path[1 ..]);
+		}
+	}
+
+	path.into()
+}
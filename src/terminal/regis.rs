@@ -0,0 +1,814 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::f64::consts::PI;
+use std::str;
+use std::cmp;
+use std::collections::HashSet;
+use std::hash::BuildHasherDefault;
+use fnv::FnvHasher;
+
+use picto::color::{Rgba, Hsl, RgbHue};
+use sys::cairo;
+
+/// The default VT340 color map, in percentages.
+const PALETTE: [(u8, u8, u8); 16] = [
+	(0, 0, 0), (20, 20, 80), (80, 13, 13), (20, 80, 20),
+	(80, 20, 80), (20, 80, 80), (80, 80, 20), (53, 53, 53),
+	(26, 26, 26), (33, 33, 60), (60, 26, 26), (33, 60, 33),
+	(60, 33, 60), (33, 60, 60), (60, 60, 33), (80, 80, 80),
+];
+
+/// The height of standard text, in screen coordinates.
+const TEXT: f64 = 20.0;
+
+/// How drawing affects the screen.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+enum Writing {
+	Overlay,
+	Replace,
+	Erase,
+	Complement,
+}
+
+/// A coordinate component, either absolute or relative to the current
+/// position.
+#[derive(PartialEq, Copy, Clone, Debug)]
+enum Coordinate {
+	Absolute(f64),
+	Relative(f64),
+}
+
+/// A ReGIS interpreter drawing on an offscreen canvas.
+#[derive(Debug)]
+pub struct Regis {
+	canvas: cairo::Surface,
+	size:   (u32, u32),
+	screen: (f64, f64, f64, f64),
+
+	command:  Option<char>,
+	position: (f64, f64),
+	stack:    Vec<((f64, f64), bool)>,
+	curve:    Option<(Vec<(f64, f64)>, bool)>,
+	center:   bool,
+	arc:      Option<f64>,
+
+	palette:    [Rgba<f64>; 16],
+	foreground: Rgba<f64>,
+	background: Option<Rgba<f64>>,
+	writing:    Writing,
+	text:       f64,
+
+	cells: HashSet<(u32, u32), BuildHasherDefault<FnvHasher>>,
+	dirty: Option<(f64, f64, f64, f64)>,
+}
+
+/// Parse the header of a ReGIS device control string, returning the rest of
+/// the input and the mode.
+pub fn header(input: &[u8]) -> Option<(&[u8], u32)> {
+	let length = input.iter().take_while(|&&b| (b >= b'0' && b <= b'9') || b == b';').count();
+
+	if input.get(length) != Some(&b'p') {
+		return None;
+	}
+
+	let mode = try!(option str::from_utf8(&input[.. length]).ok())
+		.split(';').next().and_then(|v| v.parse().ok()).unwrap_or(0);
+
+	Some((&input[length + 1 ..], mode))
+}
+
+impl Regis {
+	/// Create a new interpreter drawing on a canvas of the given size in pixels.
+	pub fn new(size: (u32, u32)) -> Self {
+		let mut palette = [Rgba::new_u8(0, 0, 0, 255); 16];
+
+		for (i, &(r, g, b)) in PALETTE.iter().enumerate() {
+			palette[i] = percent(r, g, b);
+		}
+
+		Regis {
+			canvas: cairo::Surface::image(size.0, size.1),
+			size:   size,
+			screen: (0.0, 0.0, 799.0, 479.0),
+
+			command:  None,
+			position: (0.0, 0.0),
+			stack:    Vec::new(),
+			curve:    None,
+			center:   false,
+			arc:      None,
+
+			foreground: palette[7],
+			background: None,
+			palette:    palette,
+			writing:    Writing::Overlay,
+			text:       1.0,
+
+			cells: Default::default(),
+			dirty: None,
+		}
+	}
+
+	/// Get the size of the canvas in pixels.
+	pub fn size(&self) -> (u32, u32) {
+		self.size
+	}
+
+	/// Get the current position, in screen coordinates.
+	pub fn position(&self) -> (f64, f64) {
+		self.position
+	}
+
+	/// Get the whole canvas as an image.
+	pub fn image(&self) -> cairo::Image {
+		self.canvas.to_image((0, 0, self.size.0, self.size.1))
+	}
+
+	/// Resize the canvas to the given size in pixels, keeping what has been
+	/// drawn, cells of the given size that fall outside are forgotten.
+	pub fn resize(&mut self, size: (u32, u32), (width, height): (u32, u32)) {
+		let canvas = cairo::Surface::image(size.0, size.1);
+		cairo::Context::new(&canvas).image(&self.image(), 0.0, 0.0);
+		canvas.flush();
+
+		self.canvas = canvas;
+		self.size   = size;
+		self.cells.retain(|&(x, y)| (x + 1) * width <= size.0 && (y + 1) * height <= size.1);
+	}
+
+	/// Get the cells of the given size that changed since the last call, `None`
+	/// means the cell is now empty.
+	pub fn cells(&mut self, (width, height): (u32, u32)) -> Vec<((u32, u32), Option<cairo::Image>)> {
+		let mut result = Vec::new();
+
+		let (x1, y1, x2, y2) = match self.dirty.take() {
+			Some(area) => area,
+			None       => return result,
+		};
+
+		// Only the cells within the changed area are looked at.
+		let (w, h)  = (self.size.0 as f64, self.size.1 as f64);
+		let columns = cmp::min(self.size.0 / width, x2.max(0.0).min(w) as u32 / width + 1);
+		let rows    = cmp::min(self.size.1 / height, y2.max(0.0).min(h) as u32 / height + 1);
+
+		for y in (y1.max(0.0).min(h) as u32 / height) .. rows {
+			for x in (x1.max(0.0).min(w) as u32 / width) .. columns {
+				let image = self.canvas.to_image((x * width, y * height, width, height));
+
+				if image.as_ref().iter().any(|&b| b != 0) {
+					self.cells.insert((x, y));
+					result.push(((x, y), Some(image)));
+				}
+				else if self.cells.remove(&(x, y)) {
+					result.push(((x, y), None));
+				}
+			}
+		}
+
+		result
+	}
+
+	/// Interpret the given commands, returning any report to send back.
+	pub fn handle(&mut self, input: &str) -> Vec<String> {
+		let mut reports = Vec::new();
+		let mut context = cairo::Context::new(&self.canvas);
+		let mut parser  = Parser::new(input);
+
+		while let Some(ch) = parser.next() {
+			match ch {
+				'[' => {
+					let coordinate = parser.coordinate();
+					self.coordinate(&mut context, coordinate);
+				}
+
+				'(' => {
+					let group = parser.group();
+					self.options(&mut context, &group, &mut reports);
+				}
+
+				'\'' | '"' => {
+					let string = parser.string(ch);
+
+					if self.command == Some('T') {
+						self.write(&mut context, &string);
+					}
+				}
+
+				// Macrographs are not supported, skip their definitions.
+				'@' => {
+					if parser.next() == Some(':') {
+						parser.macrograph();
+					}
+				}
+
+				ch if ch.is_ascii_alphabetic() => {
+					self.command = Some(ch.to_ascii_uppercase());
+					self.center  = false;
+					self.arc     = None;
+				}
+
+				_ => ()
+			}
+		}
+
+		self.canvas.flush();
+		reports
+	}
+
+	/// Handle a coordinate for the current command.
+	fn coordinate(&mut self, o: &mut cairo::Context, (x, y): (Option<Coordinate>, Option<Coordinate>)) {
+		let point = (resolve(self.position.0, x), resolve(self.position.1, y));
+
+		match self.command {
+			Some('P') => {
+				self.position = point;
+			}
+
+			Some('V') => {
+				let from = self.position;
+				self.line(o, from, point);
+				self.position = point;
+			}
+
+			Some('C') => {
+				if let Some((ref mut points, _)) = self.curve {
+					points.push(point);
+					self.position = point;
+
+					return;
+				}
+
+				let (center, edge) = if self.center {
+					(point, self.position)
+				}
+				else {
+					(self.position, point)
+				};
+
+				let radius = ((edge.0 - center.0).powi(2) + (edge.1 - center.1).powi(2)).sqrt();
+				let start  = (edge.1 - center.1).atan2(edge.0 - center.0);
+
+				// Positive degrees go counterclockwise, which on screen means going
+				// against the angle.
+				let end = start - self.arc.unwrap_or(360.0) * PI / 180.0;
+				self.circle(o, center, radius, start, end);
+
+				if self.center && self.arc.is_some() {
+					self.position = (center.0 + radius * end.cos(), center.1 + radius * end.sin());
+				}
+			}
+
+			_ => ()
+		}
+	}
+
+	/// Handle options for the current command.
+	fn options(&mut self, o: &mut cairo::Context, group: &str, reports: &mut Vec<String>) {
+		for (key, value) in options(group) {
+			match (self.command.unwrap_or(' '), key) {
+				// Temporary write controls, treated as permanent.
+				(_, 'W') =>
+					self.controls(&value),
+
+				('P', 'B') | ('P', 'S') | ('V', 'B') | ('V', 'S') => {
+					self.stack.push((self.position, key == 'B'));
+				}
+
+				('P', 'E') => {
+					if let Some((position, _)) = self.stack.pop() {
+						self.position = position;
+					}
+				}
+
+				('V', 'E') => {
+					if let Some((position, bounded)) = self.stack.pop() {
+						if bounded {
+							let from = self.position;
+							self.line(o, from, position);
+							self.position = position;
+						}
+					}
+				}
+
+				('C', 'C') =>
+					self.center = true,
+
+				('C', 'A') =>
+					self.arc = Some(value.trim().parse().unwrap_or(0.0)),
+
+				('C', 'B') | ('C', 'S') => {
+					self.curve = Some((vec![self.position], key == 'B'));
+				}
+
+				('C', 'E') => {
+					if let Some((points, bounded)) = self.curve.take() {
+						self.interpolate(o, points, bounded);
+					}
+				}
+
+				('T', 'S') =>
+					self.text = value.trim().parse::<f64>().map(|v| v.max(1.0)).unwrap_or(1.0),
+
+				('W', _) =>
+					self.control(key, &value),
+
+				('S', _) =>
+					self.screen(o, key, &value),
+
+				('R', 'P') => {
+					reports.push(format!("[{},{}]\r", self.position.0 as i64, self.position.1 as i64));
+				}
+
+				(command, key) =>
+					debug!(target: "cancer::terminal::regis", "unhandled option: {}({}{})", command, key, value)
+			}
+		}
+	}
+
+	/// Handle a group of write controls.
+	fn controls(&mut self, group: &str) {
+		let group = group.trim_left_matches('(').trim_right_matches(')');
+
+		for (key, value) in options(group) {
+			self.control(key, &value);
+		}
+	}
+
+	/// Handle a single write control.
+	fn control(&mut self, key: char, value: &str) {
+		match key {
+			'I' => {
+				if let Some(color) = self.color(value) {
+					self.foreground = color;
+				}
+			}
+
+			'V' => self.writing = Writing::Overlay,
+			'R' => self.writing = Writing::Replace,
+			'E' => self.writing = Writing::Erase,
+			'C' => self.writing = Writing::Complement,
+
+			key =>
+				debug!(target: "cancer::terminal::regis", "unhandled write control: {}{}", key, value)
+		}
+	}
+
+	/// Handle a screen option.
+	fn screen(&mut self, o: &mut cairo::Context, key: char, value: &str) {
+		match key {
+			'E' => {
+				o.save();
+				o.operator(cairo::Operator::Source);
+				o.rgba(&self.background.unwrap_or(Rgba::new_u8(0, 0, 0, 0)));
+				o.paint();
+				o.restore();
+
+				let size = (self.size.0 as f64, self.size.1 as f64);
+				self.damage((0.0, 0.0), size);
+			}
+
+			'I' => {
+				if let Some(color) = self.color(value) {
+					self.background = Some(color);
+				}
+			}
+
+			'A' => {
+				let mut parser = Parser::new(value);
+				let mut points = Vec::new();
+
+				while let Some(ch) = parser.next() {
+					if ch == '[' {
+						let (x, y) = parser.coordinate();
+						points.push((resolve(0.0, x), resolve(0.0, y)));
+					}
+				}
+
+				if points.len() == 2 && points[0].0 != points[1].0 && points[0].1 != points[1].1 {
+					self.screen = (points[0].0, points[0].1, points[1].0, points[1].1);
+				}
+			}
+
+			'M' => {
+				let index = value.chars().take_while(|c| c.is_digit(10)).collect::<String>();
+				let color = &value[index.len() ..];
+
+				if let (Ok(index), Some(color)) = (index.parse::<usize>(), self.color(color)) {
+					if index < self.palette.len() {
+						self.palette[index] = color;
+					}
+				}
+			}
+
+			key =>
+				debug!(target: "cancer::terminal::regis", "unhandled screen option: {}{}", key, value)
+		}
+	}
+
+	/// Parse a color specifier.
+	fn color(&self, value: &str) -> Option<Rgba<f64>> {
+		let value = value.trim().trim_left_matches('(').trim_right_matches(')').trim();
+
+		if let Ok(index) = value.parse::<usize>() {
+			return Some(self.palette[index % self.palette.len()]);
+		}
+
+		let options = options(value);
+
+		if options.iter().any(|&(k, _)| k == 'H') {
+			let get = |key| options.iter().find(|&&(k, _)| k == key)
+				.and_then(|&(_, ref v)| v.trim().parse::<f32>().ok()).unwrap_or(0.0);
+
+			// Hues start from blue instead of red.
+			let (r, g, b, a) = Rgba::from(Hsl::new(RgbHue::from_radians(((get('H') + 240.0) % 360.0) * PI as f32 / 180.0),
+				get('S') / 100.0, get('L') / 100.0)).to_pixel();
+
+			return Some(Rgba::new_u8(r, g, b, a));
+		}
+
+		match options.first().map(|&(k, _)| k) {
+			Some('D') => Some(percent(0, 0, 0)),
+			Some('R') => Some(percent(100, 0, 0)),
+			Some('G') => Some(percent(0, 100, 0)),
+			Some('B') => Some(percent(0, 0, 100)),
+			Some('C') => Some(percent(0, 100, 100)),
+			Some('Y') => Some(percent(100, 100, 0)),
+			Some('M') => Some(percent(100, 0, 100)),
+			Some('W') => Some(percent(100, 100, 100)),
+			_         => None,
+		}
+	}
+
+	/// Convert screen coordinates to canvas pixels.
+	fn pixel(&self, (x, y): (f64, f64)) -> (f64, f64) {
+		let (x0, y0, x1, y1) = self.screen;
+
+		((x - x0) * self.size.0 as f64 / (x1 - x0 + 1.0),
+		 (y - y0) * self.size.1 as f64 / (y1 - y0 + 1.0))
+	}
+
+	/// Get the scaling from screen coordinates to pixels.
+	fn scale(&self) -> (f64, f64) {
+		let (x0, y0, x1, y1) = self.screen;
+
+		((self.size.0 as f64 / (x1 - x0 + 1.0)).abs(),
+		 (self.size.1 as f64 / (y1 - y0 + 1.0)).abs())
+	}
+
+	/// Prepare the context for drawing with the current write controls.
+	fn ink(&self, o: &mut cairo::Context) {
+		o.line_width(self.scale().0.max(1.0));
+
+		match self.writing {
+			Writing::Overlay | Writing::Replace => {
+				o.rgba(&self.foreground);
+			}
+
+			Writing::Erase => {
+				if let Some(background) = self.background {
+					o.rgba(&background);
+				}
+				else {
+					o.operator(cairo::Operator::Clear);
+				}
+			}
+
+			Writing::Complement => {
+				o.operator(cairo::Operator::Difference);
+				o.rgba(&Rgba::new_u8(255, 255, 255, 255));
+			}
+		}
+	}
+
+	/// Draw a line, or a dot if both ends are the same.
+	fn line(&mut self, o: &mut cairo::Context, from: (f64, f64), to: (f64, f64)) {
+		let (x1, y1) = self.pixel(from);
+		let (x2, y2) = self.pixel(to);
+
+		o.save();
+		o.new_path();
+		self.ink(o);
+
+		if from == to {
+			let (w, h) = self.scale();
+			o.rectangle(x1, y1, w.max(1.0), h.max(1.0));
+			o.fill();
+
+			self.damage((x1, y1), (x1 + w.max(1.0), y1 + h.max(1.0)));
+		}
+		else {
+			o.move_to(x1 + 0.5, y1 + 0.5);
+			o.line_to(x2 + 0.5, y2 + 0.5);
+			o.stroke();
+
+			self.damage((x1, y1), (x2, y2));
+		}
+
+		o.restore();
+	}
+
+	/// Draw a circle or an arc.
+	fn circle(&mut self, o: &mut cairo::Context, center: (f64, f64), radius: f64, start: f64, end: f64) {
+		let (x, y) = self.pixel(center);
+		let radius = radius * self.scale().0;
+
+		o.save();
+		o.new_path();
+		self.ink(o);
+		o.arc(x, y, radius, start, end);
+		o.stroke();
+		o.restore();
+
+		self.damage((x - radius, y - radius), (x + radius, y + radius));
+	}
+
+	/// Draw a smooth curve through the points.
+	fn interpolate(&mut self, o: &mut cairo::Context, mut points: Vec<(f64, f64)>, bounded: bool) {
+		if bounded {
+			let first = points[0];
+			points.push(first);
+		}
+
+		if points.len() < 2 {
+			return;
+		}
+
+		let points = points.into_iter().map(|p| self.pixel(p)).collect::<Vec<_>>();
+		let last   = points.len() - 1;
+
+		o.save();
+		o.new_path();
+		self.ink(o);
+		o.move_to(points[0].0, points[0].1);
+
+		// Catmull-Rom splines, turned into Bézier curves.
+		for i in 0 .. last {
+			let p0 = points[if i == 0 { 0 } else { i - 1 }];
+			let p1 = points[i];
+			let p2 = points[i + 1];
+			let p3 = points[if i + 2 > last { last } else { i + 2 }];
+
+			let c1 = (p1.0 + (p2.0 - p0.0) / 6.0, p1.1 + (p2.1 - p0.1) / 6.0);
+			let c2 = (p2.0 - (p3.0 - p1.0) / 6.0, p2.1 - (p3.1 - p1.1) / 6.0);
+			o.curve_to(c1.0, c1.1, c2.0, c2.1, p2.0, p2.1);
+
+			// The curve stays within its control points.
+			self.damage(p1, p2);
+			self.damage(c1, c2);
+		}
+
+		o.stroke();
+		o.restore();
+	}
+
+	/// Draw text at the current position, moving it after the text.
+	fn write(&mut self, o: &mut cairo::Context, text: &str) {
+		let size   = TEXT * self.text * self.scale().1;
+		let (x, y) = self.pixel(self.position);
+
+		o.save();
+		o.new_path();
+		self.ink(o);
+		o.move_to(x, y + size * 0.8);
+		o.text(size, text);
+
+		let (end, _) = o.current_point();
+		o.restore();
+
+		self.damage((x, y), (end, y + size));
+		self.position.0 = self.screen.0 + end / self.scale().0;
+	}
+
+	/// Mark the area between the given pixels as changed, with room for the
+	/// line width.
+	fn damage(&mut self, (x1, y1): (f64, f64), (x2, y2): (f64, f64)) {
+		let margin = self.scale().0.max(1.0) + 1.0;
+		let area   = (x1.min(x2) - margin, y1.min(y2) - margin, x1.max(x2) + margin, y1.max(y2) + margin);
+
+		self.dirty = Some(match self.dirty {
+			Some((a, b, c, d)) => (a.min(area.0), b.min(area.1), c.max(area.2), d.max(area.3)),
+			None               => area,
+		});
+	}
+}
+
+/// Turn percentages into a color.
+fn percent(r: u8, g: u8, b: u8) -> Rgba<f64> {
+	Rgba::new_u8(
+		(r as u32 * 255 / 100) as u8,
+		(g as u32 * 255 / 100) as u8,
+		(b as u32 * 255 / 100) as u8,
+		255)
+}
+
+/// Resolve a coordinate component against the current value.
+fn resolve(current: f64, value: Option<Coordinate>) -> f64 {
+	match value {
+		Some(Coordinate::Absolute(v)) => v,
+		Some(Coordinate::Relative(v)) => current + v,
+		None                          => current,
+	}
+}
+
+/// Split an option group into letters and their values.
+fn options(group: &str) -> Vec<(char, String)> {
+	let mut result  = Vec::new();
+	let mut current = None;
+	let mut value   = String::new();
+	let mut depth   = 0;
+	let mut quote   = None;
+
+	for ch in group.chars() {
+		if let Some(q) = quote {
+			value.push(ch);
+
+			if ch == q {
+				quote = None;
+			}
+
+			continue;
+		}
+
+		match ch {
+			'(' | '[' => {
+				depth += 1;
+				value.push(ch);
+			}
+
+			')' | ']' => {
+				depth -= 1;
+				value.push(ch);
+			}
+
+			'\'' | '"' => {
+				quote = Some(ch);
+				value.push(ch);
+			}
+
+			ch if depth == 0 && ch.is_ascii_alphabetic() => {
+				if let Some(key) = current.take() {
+					result.push((key, value.clone()));
+				}
+
+				current = Some(ch.to_ascii_uppercase());
+				value.clear();
+			}
+
+			ch if ch.is_whitespace() || ch == ',' && depth == 0 =>
+				(),
+
+			ch =>
+				value.push(ch),
+		}
+	}
+
+	if let Some(key) = current {
+		result.push((key, value));
+	}
+
+	result
+}
+
+/// A simple character parser.
+struct Parser {
+	input: Vec<char>,
+	index: usize,
+}
+
+impl Parser {
+	fn new(input: &str) -> Self {
+		Parser {
+			input: input.chars().collect(),
+			index: 0,
+		}
+	}
+
+	fn peek(&self) -> Option<char> {
+		self.input.get(self.index).cloned()
+	}
+
+	fn next(&mut self) -> Option<char> {
+		let ch = self.peek();
+		self.index += 1;
+		ch
+	}
+
+	/// Parse a coordinate after the opening bracket.
+	fn coordinate(&mut self) -> (Option<Coordinate>, Option<Coordinate>) {
+		let mut parts = (None, None);
+		let mut first = true;
+		let mut value = String::new();
+
+		fn parse(value: &str) -> Option<Coordinate> {
+			let value = value.trim();
+
+			if value.starts_with('+') || value.starts_with('-') {
+				value.parse().ok().map(Coordinate::Relative)
+			}
+			else {
+				value.parse().ok().map(Coordinate::Absolute)
+			}
+		}
+
+		while let Some(ch) = self.next() {
+			match ch {
+				',' if first => {
+					parts.0 = parse(&value);
+					first   = false;
+					value.clear();
+				}
+
+				']' =>
+					break,
+
+				ch =>
+					value.push(ch),
+			}
+		}
+
+		if first {
+			parts.0 = parse(&value);
+		}
+		else {
+			parts.1 = parse(&value);
+		}
+
+		parts
+	}
+
+	/// Get the content of a group after the opening parenthesis.
+	fn group(&mut self) -> String {
+		let mut depth  = 1;
+		let mut result = String::new();
+		let mut quote  = None;
+
+		while let Some(ch) = self.next() {
+			if let Some(q) = quote {
+				if ch == q {
+					quote = None;
+				}
+			}
+			else {
+				match ch {
+					'\'' | '"' => quote = Some(ch),
+					'('        => depth += 1,
+					')'        => depth -= 1,
+					_          => (),
+				}
+
+				if depth == 0 {
+					break;
+				}
+			}
+
+			result.push(ch);
+		}
+
+		result
+	}
+
+	/// Get a quoted string, where doubled quotes are escapes.
+	fn string(&mut self, quote: char) -> String {
+		let mut result = String::new();
+
+		while let Some(ch) = self.next() {
+			if ch == quote {
+				if self.peek() == Some(quote) {
+					self.index += 1;
+				}
+				else {
+					break;
+				}
+			}
+
+			result.push(ch);
+		}
+
+		result
+	}
+
+	/// Skip a macrograph definition.
+	fn macrograph(&mut self) {
+		while let Some(ch) = self.next() {
+			if ch == '@' && self.peek() == Some(';') {
+				self.index += 1;
+				break;
+			}
+		}
+	}
+}
@@ -16,6 +16,7 @@
 // along with cancer.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::VecDeque;
+use std::mem;
 use std::ops::{Deref, DerefMut};
 
 use terminal::Cell;
@@ -28,10 +29,23 @@ pub struct Row {
 }
 
 impl Row {
+	/// Create an unwrapped `Row` of empty cells.
+	pub fn empty(columns: usize) -> Self {
+		Row {
+			inner:   vec_deque![Cell::default(); columns],
+			wrapped: false,
+		}
+	}
+
 	/// Check if the `Row` is wrapping.
 	pub fn is_wrapped(&self) -> bool {
 		self.wrapped
 	}
+
+	/// Get the approximate memory used by the `Row`.
+	pub fn size(&self) -> usize {
+		mem::size_of::<Row>() + self.inner.iter().map(Cell::size).sum::<usize>()
+	}
 }
 
 impl Deref for Row {
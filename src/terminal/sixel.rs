@@ -19,11 +19,15 @@ use std::collections::HashMap;
 use std::hash::BuildHasherDefault;
 use fnv::FnvHasher;
 use std::f32;
+use std::cmp;
 
 use picto::color::{Rgba, Hsl, RgbHue};
 use control::DEC::SIXEL;
 use sys::cairo;
 
+/// The color registers.
+pub type Colors = HashMap<u32, (u8, u8, u8, u8), BuildHasherDefault<FnvHasher>>;
+
 #[derive(Debug)]
 pub struct Sixel {
 	raster: SIXEL::Header,
@@ -32,9 +36,11 @@ pub struct Sixel {
 	grid:     Vec<Vec<cairo::Image>>,
 	cell:     (u32, u32),
 	limit:    (u32, u32),
+	maximum:  (u32, u32),
 	position: (u32, u32),
 
-	colors:     HashMap<u32, (u8, u8, u8, u8), BuildHasherDefault<FnvHasher>>,
+	colors:     Colors,
+	registers:  u32,
 	color:      (u8, u8, u8, u8),
 	background: (u8, u8, u8, u8),
 }
@@ -48,9 +54,11 @@ impl Sixel {
 			grid:     Default::default(),
 			cell:     cell,
 			limit:    limit,
+			maximum:  (u32::max_value(), u32::max_value()),
 			position: (0, 0),
 
 			colors:     Default::default(),
+			registers:  u32::max_value(),
 			color:      (0, 0, 0, 255),
 			background: (
 				(background.red   * 255.0) as u8,
@@ -72,12 +80,69 @@ impl Sixel {
 		self.grid
 	}
 
+	/// Get the color registers.
+	pub fn colors(&self) -> &Colors {
+		&self.colors
+	}
+
+	/// Start from the given color registers, used when they're shared.
+	pub fn shared(&mut self, colors: Colors) {
+		self.colors = colors;
+	}
+
+	/// Set the number of available color registers.
+	pub fn registers(&mut self, count: u32) {
+		self.registers = cmp::max(1, count);
+	}
+
+	/// Set the maximum size in pixels of the image.
+	pub fn maximum(&mut self, size: (u32, u32)) {
+		self.maximum = size;
+	}
+
+	/// Fill the declared raster size with the background.
+	pub fn fill(&mut self, (width, height): (u32, u32)) {
+		let width  = cmp::min(width, self.maximum.0);
+		let height = cmp::min(height, self.maximum.1);
+
+		for y in 0 .. height {
+			for x in 0 .. width {
+				// Bail out early if the cell is beyond the terminal limit.
+				if (x / self.cell.0) + self.limit.0 >= self.limit.1 {
+					break;
+				}
+
+				let background = self.background;
+				let (w, h)     = self.cell;
+				self.at(x, y).set(x % w, y % h, &background);
+			}
+		}
+	}
+
+	/// Get the cell image containing the given pixel, extending the grid as
+	/// needed.
+	fn at(&mut self, x: u32, y: u32) -> &mut cairo::Image {
+		let (x, y) = ((x / self.cell.0) as usize, (y / self.cell.1) as usize);
+
+		// If the grid doesn't have enough rows, extend it.
+		while y >= self.grid.len() {
+			self.grid.push(Vec::new());
+		}
+
+		// If the grid doesn't have enough columns, extend it.
+		while x >= self.grid[y].len() {
+			self.grid[y].push(cairo::Image::new(self.cell.0, self.cell.1));
+		}
+
+		&mut self.grid[y][x]
+	}
+
 	pub fn aspect(&mut self, aspect: (u32, u32)) {
 		self.raster.aspect = aspect;
 	}
 
 	pub fn enable(&mut self, id: u32) {
-		self.color = self.colors.get(&id).unwrap_or(&self.background).clone();
+		self.color = self.colors.get(&(id % self.registers)).unwrap_or(&self.background).clone();
 	}
 
 	pub fn define(&mut self, id: u32, color: SIXEL::Color) {
@@ -93,7 +158,7 @@ impl Sixel {
 				(r, g, b, a),
 		};
 
-		self.colors.insert(id, color);
+		self.colors.insert(id % self.registers, color);
 	}
 
 	pub fn start(&mut self) {
@@ -115,43 +180,39 @@ impl Sixel {
 
 		for _ in 0 .. times {
 			// The X within the local grid.
-			let x = (self.position.0 / self.cell.0) as usize;
+			let x = self.position.0 / self.cell.0;
 
-			// Bail out early if the cell is beyond the terminal limit.
-			if x as u32 + self.limit.0 >= self.limit.1 {
+			// Bail out early if the cell is beyond the terminal or geometry limit.
+			if x + self.limit.0 >= self.limit.1 || self.position.0 >= self.maximum.0 {
 				break;
 			}
 
 			// The X within the image buffer.
-			let xo = self.position.0 % self.cell.0;
+			let xo       = self.position.0 % self.cell.0;
+			let position = self.position.0;
 
 			for (i, y) in (self.position.1 .. self.position.1 + (6 * self.raster.aspect.0)).enumerate() {
+				// Anything beyond the maximum height is dropped.
+				if y >= self.maximum.1 {
+					break;
+				}
+
 				// The bit index within the sixel map.
 				let bit = (i as u32 / self.raster.aspect.0) as u8;
 
 				// The Y within the image buffer.
 				let yo = y as u32 % self.cell.1;
 
-				// The Y within the grid.
-				let y = (y / self.cell.1) as usize;
-
-				// If the grid doesn't have enough rows, extend it.
-				while y >= self.grid.len() {
-					self.grid.push(Vec::new());
-				}
-
-				// If the grid doesn't have enough columns, extend it.
-				while x >= self.grid[y].len() {
-					self.grid[y].push(cairo::Image::new(self.cell.0, self.cell.1));
-				}
+				let (color, background, fill) = (self.color, self.background, self.raster.background);
+				let image                     = self.at(position, y);
 
 				// If the bit is enabled, set it.
 				if value.get(bit) {
-					self.grid[y][x].set(xo, yo, &self.color);
+					image.set(xo, yo, &color);
 				}
 				// If disabled bits should set the background color, do so.
-				else if self.raster.background {
-					self.grid[y][x].set(xo, yo, &self.background);
+				else if fill {
+					image.set(xo, yo, &background);
 				}
 			}
 
@@ -161,8 +222,14 @@ impl Sixel {
 
 	pub fn handle(&mut self, item: &SIXEL::T) {
 		match *item {
-			SIXEL::Raster { aspect, .. } => {
+			SIXEL::Raster { aspect, size } => {
 				self.aspect(aspect);
+
+				// The declared size is filled with the background, even if nothing
+				// is drawn on it.
+				if self.raster.background {
+					self.fill(size);
+				}
 			}
 
 			SIXEL::Enable(id) => {
@@ -0,0 +1,258 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::char;
+use std::str;
+use std::cmp;
+
+/// Soft characters are stored in the cells as private use characters
+/// starting from here.
+const BASE: u32 = 0x100000;
+
+/// The most soft character sets kept at once.
+const FONTS: usize = 32;
+
+/// The largest character matrix, as on the VT510.
+const WIDTH: u32  = 15;
+const HEIGHT: u32 = 16;
+
+/// The soft character sets loaded through DECDLD.
+#[derive(Default, Debug)]
+pub struct Soft {
+	fonts: Vec<Font>,
+}
+
+/// A single soft character set.
+#[derive(Debug)]
+pub struct Font {
+	designation: String,
+	full:        bool,
+	glyphs:      Vec<Option<Glyph>>,
+}
+
+/// A bitmap glyph.
+#[derive(Clone, Debug)]
+pub struct Glyph {
+	width:  u32,
+	height: u32,
+	bits:   Vec<bool>,
+}
+
+impl Glyph {
+	pub fn width(&self) -> u32 {
+		self.width
+	}
+
+	pub fn height(&self) -> u32 {
+		self.height
+	}
+
+	/// Check if the given pixel is set.
+	pub fn get(&self, x: u32, y: u32) -> bool {
+		self.bits[(y * self.width + x) as usize]
+	}
+}
+
+/// Parse the header of a DECDLD device control string, returning the rest of
+/// the input and the parameters.
+pub fn header(input: &[u8]) -> Option<(&[u8], Vec<u32>)> {
+	let length = input.iter().take_while(|&&b| (b >= b'0' && b <= b'9') || b == b';').count();
+
+	if input.get(length) != Some(&b'{') {
+		return None;
+	}
+
+	let params = try!(option str::from_utf8(&input[.. length]).ok())
+		.split(';').map(|v| v.parse().unwrap_or(0)).collect();
+
+	Some((&input[length + 1 ..], params))
+}
+
+impl Soft {
+	/// Check if no soft character set is loaded.
+	pub fn is_empty(&self) -> bool {
+		self.fonts.is_empty()
+	}
+
+	/// Load a soft character set from the DECDLD parameters and body.
+	pub fn load(&mut self, params: &[u32], body: &str) {
+		let param = |i: usize| params.get(i).cloned().unwrap_or(0);
+
+		let start = param(1);
+		let erase = param(2);
+		let full  = param(7) == 1;
+
+		// The matrix size either comes from the VT220 presets, or is explicit.
+		let height          = if param(6) == 0 { HEIGHT } else { cmp::min(param(6), HEIGHT) };
+		let (width, height) = match param(3) {
+			2     => (5, 10),
+			3     => (6, 10),
+			4     => (7, 10),
+			0 | 1 => (if param(4) == 2 { 6 } else { 10 }, height),
+			n     => (cmp::min(n, WIDTH), height),
+		};
+
+		// The designation is any number of intermediates followed by a final.
+		let intermediates = body.bytes().take_while(|&b| b >= 0x20 && b <= 0x2F).count();
+		if body.len() <= intermediates {
+			return;
+		}
+
+		let designation = body[.. intermediates + 1].to_owned();
+		let data        = &body[intermediates + 1 ..];
+
+		if erase == 2 {
+			self.fonts.clear();
+		}
+
+		let index = if let Some(index) = self.find(&designation) {
+			index
+		}
+		else if self.fonts.len() >= FONTS {
+			debug!(target: "cancer::terminal::soft", "too many soft character sets, ignoring {:?}", designation);
+			return;
+		}
+		else {
+			self.fonts.push(Font {
+				designation: designation.clone(),
+				full:        full,
+				glyphs:      vec![None; 96],
+			});
+
+			self.fonts.len() - 1
+		};
+
+		let font = &mut self.fonts[index];
+		font.full = full;
+
+		if erase == 0 {
+			for glyph in &mut font.glyphs {
+				*glyph = None;
+			}
+		}
+
+		// A 94 character set can't replace the space.
+		let mut code = start as usize + if full || start > 0 { 0 } else { 1 };
+
+		for definition in data.split(';') {
+			if code >= font.glyphs.len() {
+				break;
+			}
+
+			let mut bits = vec![false; (width * height) as usize];
+
+			for (row, sixels) in definition.split('/').enumerate() {
+				for (x, byte) in sixels.bytes().filter(|&b| b >= 0x3F && b <= 0x7E).enumerate() {
+					if x as u32 >= width {
+						break;
+					}
+
+					for bit in 0 .. 6 {
+						let y = row as u32 * 6 + bit;
+
+						if y < height && (byte - 0x3F) & (1 << bit) != 0 {
+							bits[(y * width + x as u32) as usize] = true;
+						}
+					}
+				}
+			}
+
+			font.glyphs[code] = Some(Glyph {
+				width:  width,
+				height: height,
+				bits:   bits,
+			});
+
+			code += 1;
+		}
+	}
+
+	/// Find the soft character set with the given designation.
+	pub fn find(&self, designation: &str) -> Option<usize> {
+		self.fonts.iter().position(|f| f.designation == designation)
+	}
+
+	/// Get the designation of the given soft character set, and whether it's a
+	/// 96 character set.
+	pub fn designation(&self, font: usize) -> (&str, bool) {
+		self.fonts.get(font).map(|f| (&*f.designation, f.full)).unwrap_or(("B", false))
+	}
+
+	/// Parse a designation of a soft character set, returning the rest of the
+	/// input, the G-set index and the font.
+	pub fn designate<'a>(&self, input: &'a [u8]) -> Option<(&'a [u8], usize, usize)> {
+		if self.fonts.is_empty() || input.len() < 3 || input[0] != 0x1B {
+			return None;
+		}
+
+		let (slot, full) = match input[1] {
+			b'(' => (0, false),
+			b')' => (1, false),
+			b'*' => (2, false),
+			b'+' => (3, false),
+			b'-' => (1, true),
+			b'.' => (2, true),
+			b'/' => (3, true),
+			_    => return None,
+		};
+
+		let rest          = &input[2 ..];
+		let intermediates = rest.iter().take_while(|&&b| b >= 0x20 && b <= 0x2F).count();
+		let designation   = try!(option rest.get(.. intermediates + 1));
+
+		if designation[intermediates] < 0x30 || designation[intermediates] > 0x7E {
+			return None;
+		}
+
+		let font = try!(option self.find(try!(option str::from_utf8(designation).ok())));
+
+		if self.fonts[font].full != full {
+			return None;
+		}
+
+		Some((&rest[intermediates + 1 ..], slot, font))
+	}
+
+	/// Translate a character to the private use character standing for its
+	/// soft glyph, if any.
+	pub fn translate(&self, font: usize, ch: char) -> char {
+		let code = (ch as u32).wrapping_sub(0x20) as usize;
+
+		match self.fonts.get(font).and_then(|f| f.glyphs.get(code)) {
+			Some(&Some(_)) =>
+				char::from_u32(BASE + (font as u32) * 0x80 + code as u32).unwrap_or(ch),
+
+			_ =>
+				ch
+		}
+	}
+
+	/// Get the glyph for a private use character.
+	pub fn glyph(&self, value: &str) -> Option<&Glyph> {
+		let mut chars = value.chars();
+		let     ch    = try!(option chars.next()) as u32;
+
+		if chars.next().is_some() || ch < BASE {
+			return None;
+		}
+
+		let font = ((ch - BASE) / 0x80) as usize;
+		let code = ((ch - BASE) % 0x80) as usize;
+
+		self.fonts.get(font).and_then(|f| f.glyphs.get(code)).and_then(Option::as_ref)
+	}
+}
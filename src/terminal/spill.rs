@@ -0,0 +1,190 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+use std::io::{self, Read, Write, Seek, SeekFrom};
+use std::fs::{self, File, OpenOptions};
+use std::collections::VecDeque;
+use std::os::unix::fs::OpenOptionsExt;
+use std::process;
+use std::env;
+
+/// Rows evicted from memory, stored in private temporary files.
+///
+/// The rows are split in segments of half the limit, once the limit is
+/// exceeded the oldest segment is dropped as a whole.
+#[derive(Debug)]
+pub struct Spill {
+	segments: VecDeque<Segment>,
+	limit:    u64,
+}
+
+#[derive(Debug)]
+struct Segment {
+	file:    File,
+	offsets: Vec<u64>,
+	end:     u64,
+}
+
+impl Spill {
+	/// Create the backing storage, keeping around `limit` bytes at most.
+	pub fn new(limit: usize) -> io::Result<Self> {
+		let mut segments = VecDeque::new();
+		segments.push_back(try!(Segment::new()));
+
+		Ok(Spill {
+			segments: segments,
+			limit:    limit as u64,
+		})
+	}
+
+	/// Get the number of stored rows.
+	pub fn len(&self) -> usize {
+		self.segments.iter().map(|s| s.offsets.len()).sum()
+	}
+
+	/// Get the number of stored bytes.
+	pub fn size(&self) -> u64 {
+		self.segments.iter().map(|s| s.end).sum()
+	}
+
+	/// Append an encoded row, returns the number of rows evicted from the
+	/// front to make room for it.
+	pub fn push(&mut self, data: &[u8]) -> io::Result<usize> {
+		if self.segments.back().unwrap().end >= self.limit / 2 {
+			self.segments.push_back(try!(Segment::new()));
+		}
+
+		let mut evicted = 0;
+		while self.segments.len() > 1 && self.size() + data.len() as u64 > self.limit {
+			evicted += self.segments.pop_front().unwrap().offsets.len();
+		}
+
+		try!(self.segments.back_mut().unwrap().push(data));
+
+		Ok(evicted)
+	}
+
+	/// Read the encoded row at the given index.
+	pub fn get(&self, mut index: usize) -> io::Result<Vec<u8>> {
+		for segment in &self.segments {
+			if index < segment.offsets.len() {
+				return segment.get(index);
+			}
+
+			index -= segment.offsets.len();
+		}
+
+		panic!("spilled row out of bounds");
+	}
+
+	/// Remove and return the last row.
+	pub fn pop(&mut self) -> io::Result<Option<Vec<u8>>> {
+		while self.segments.len() > 1 && self.segments.back().unwrap().offsets.is_empty() {
+			self.segments.pop_back();
+		}
+
+		self.segments.back_mut().unwrap().pop()
+	}
+
+	/// Drop every row.
+	pub fn clear(&mut self) -> io::Result<()> {
+		self.segments.truncate(1);
+		self.segments[0].clear()
+	}
+}
+
+impl Segment {
+	/// Create the backing file, it's unlinked right away so it's gone on exit
+	/// and only ever reachable through the open descriptor.
+	fn new() -> io::Result<Self> {
+		let mut path = env::temp_dir();
+
+		for i in 0 .. {
+			path.push(format!("cancer-{}-{}", process::id(), i));
+
+			match OpenOptions::new().read(true).append(true).create_new(true).mode(0o600).open(&path) {
+				Ok(file) => {
+					try!(fs::remove_file(&path));
+
+					return Ok(Segment {
+						file:    file,
+						offsets: Vec::new(),
+						end:     0,
+					});
+				}
+
+				Err(ref err) if err.kind() == io::ErrorKind::AlreadyExists && i < 64 => {
+					path.pop();
+				}
+
+				Err(err) =>
+					return Err(err),
+			}
+		}
+
+		unreachable!()
+	}
+
+	fn push(&mut self, data: &[u8]) -> io::Result<()> {
+		// Don't leave a partially written row behind.
+		if let Err(err) = self.file.write_all(data) {
+			let _ = self.file.set_len(self.end);
+			return Err(err);
+		}
+
+		self.offsets.push(self.end);
+		self.end += data.len() as u64;
+
+		Ok(())
+	}
+
+	fn get(&self, index: usize) -> io::Result<Vec<u8>> {
+		let start = self.offsets[index];
+		let end   = self.offsets.get(index + 1).cloned().unwrap_or(self.end);
+
+		let mut buffer = vec![0; (end - start) as usize];
+		let mut file   = &self.file;
+
+		try!(file.seek(SeekFrom::Start(start)));
+		try!(file.read_exact(&mut buffer));
+
+		Ok(buffer)
+	}
+
+	fn pop(&mut self) -> io::Result<Option<Vec<u8>>> {
+		if self.offsets.is_empty() {
+			return Ok(None);
+		}
+
+		let data  = try!(self.get(self.offsets.len() - 1));
+		let start = self.offsets.pop().unwrap();
+
+		try!(self.file.set_len(start));
+		self.end = start;
+
+		Ok(Some(data))
+	}
+
+	fn clear(&mut self) -> io::Result<()> {
+		try!(self.file.set_len(0));
+
+		self.offsets.clear();
+		self.offsets.shrink_to_fit();
+		self.end = 0;
+
+		Ok(())
+	}
+}
@@ -0,0 +1,148 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::rc::Rc;
+use std::ops::Index;
+use std::cmp;
+
+use unicode_width::UnicodeWidthStr;
+use style::Style;
+use terminal::Cell;
+use overlay::Status;
+
+/// The status line below the grid, as selected by DECSSDT.
+#[derive(Debug)]
+pub enum Line {
+	/// Shows the cursor position and modes.
+	Indicator(Status),
+
+	/// Written to by the program.
+	Host(Host),
+}
+
+/// A status line the program can write to.
+#[derive(Debug)]
+pub struct Host {
+	inner: Vec<Cell>,
+	x:     u32,
+}
+
+impl Line {
+	/// Create an indicator status line.
+	pub fn indicator(style: Style, cols: u32) -> Self {
+		Line::Indicator(Status::new(style, cols))
+	}
+
+	/// Create a host writable status line.
+	pub fn host(cols: u32) -> Self {
+		Line::Host(Host {
+			inner: vec![Cell::empty(Default::default()); cols as usize],
+			x:     0,
+		})
+	}
+
+	/// Check if the program can write to the status line.
+	pub fn is_host(&self) -> bool {
+		if let Line::Host(..) = *self {
+			true
+		}
+		else {
+			false
+		}
+	}
+
+	/// Get the number of columns.
+	pub fn columns(&self) -> u32 {
+		match *self {
+			Line::Indicator(ref status) =>
+				status.len() as u32,
+
+			Line::Host(ref host) =>
+				host.inner.len() as u32,
+		}
+	}
+}
+
+impl Host {
+	/// Get the cursor column.
+	pub fn x(&self) -> u32 {
+		self.x
+	}
+
+	/// Write a character at the cursor, the cursor stops at the last column.
+	pub fn insert(&mut self, ch: &str, style: Rc<Style>) {
+		let width = cmp::max(1, ch.width() as u32);
+		let end   = self.inner.len() as u32;
+
+		// Clean up the start of an overwritten wide character.
+		let mut start = self.x as usize;
+		while start > 0 && self.inner[start].is_reference() {
+			start -= 1;
+		}
+
+		for cell in &mut self.inner[start .. self.x as usize] {
+			cell.make_empty(style.clone());
+		}
+
+		// Characters that don't fit leave the rest of the line empty.
+		if self.x + width > end {
+			for cell in &mut self.inner[self.x as usize ..] {
+				cell.make_empty(style.clone());
+			}
+		}
+		else {
+			self.inner[self.x as usize].make_occupied(ch, style.clone());
+
+			for i in 1 .. width {
+				self.inner[(self.x + i) as usize].make_reference(i as u8);
+			}
+
+			// Clean up the rest of an overwritten wide character.
+			for cell in self.inner[(self.x + width) as usize ..].iter_mut().take_while(|c| c.is_reference()) {
+				cell.make_empty(style.clone());
+			}
+		}
+
+		self.x = cmp::min(self.x + width, end - 1);
+	}
+
+	/// Move the cursor to the given column.
+	pub fn position(&mut self, x: u32) {
+		self.x = if x >= self.inner.len() as u32 { self.inner.len() as u32 - 1 } else { x };
+	}
+
+	/// Erase from the cursor to the end of the line.
+	pub fn erase(&mut self, style: Rc<Style>) {
+		for cell in &mut self.inner[self.x as usize ..] {
+			cell.make_empty(style.clone());
+		}
+	}
+}
+
+impl Index<u32> for Line {
+	type Output = Cell;
+
+	fn index(&self, x: u32) -> &Cell {
+		match *self {
+			Line::Indicator(ref status) =>
+				&status[x as usize],
+
+			Line::Host(ref host) =>
+				&host.inner[x as usize],
+		}
+	}
+}
@@ -63,30 +63,27 @@ impl Tabs {
 	pub fn next(&self, n: i32, start: u32) -> u32 {
 		let mut end = start;
 
-		if n > 0 {
-			while end < self.cols {
-				end += 1;
-
-				if self.get(end) {
-					break;
+		for _ in 0 .. n.abs() {
+			if n > 0 {
+				while end + 1 < self.cols {
+					end += 1;
+
+					if self.get(end) {
+						break;
+					}
 				}
 			}
-		}
-		else {
-			while end != 0 {
-				end -= 1;
+			else {
+				while end != 0 {
+					end -= 1;
 
-				if self.get(end) {
-					break;
+					if self.get(end) {
+						break;
+					}
 				}
 			}
 		}
 
-		if end == self.cols && !self.get(end) {
-			start
-		}
-		else {
-			end
-		}
+		end
 	}
 }
@@ -19,6 +19,7 @@ use std::ops::Index;
 use std::sync::Arc;
 use std::io::{self, Write};
 use std::mem;
+use std::iter;
 use std::vec;
 use std::str;
 use std::cmp;
@@ -35,7 +36,10 @@ use config::style::Shape;
 use style::{self, Style};
 use platform::key::{self, Key};
 use platform::mouse::{self, Mouse};
-use terminal::{Iter, Touched, Cell, Tabs, Grid, Sixel, cell};
+use terminal::{Iter, Touched, Cell, Tabs, Grid, Encoding, Soft, Printer, Logger, bidi, cell, charset, soft, status, printer};
+#[cfg(feature = "images")]
+use terminal::{Sixel, Images, Regis, image, kitty, iterm, sixel, regis};
+use terminal::encoding::Controls;
 use terminal::mode::{self, Mode};
 use terminal::cursor::{self, Cursor};
 use terminal::touched;
@@ -49,15 +53,39 @@ pub struct Terminal {
 
 	region:  Region,
 	cache:   Option<Vec<u8>>,
-	touched: Touched,
-	mode:    Mode,
-	click:   Option<mouse::Click>,
-	command: Option<Command>,
+	touched:  Touched,
+	mode:     Mode,
+	encoding: Encoding,
+	click:    Option<mouse::Click>,
+	command:  Option<Command>,
 
 	scroll: Option<u32>,
 	grid:   Grid,
 	tabs:   Tabs,
 
+	#[cfg(feature = "images")]
+	images:   Images,
+	#[cfg(feature = "images")]
+	graphics: Option<kitty::Request>,
+
+	#[cfg(feature = "images")]
+	palette:   sixel::Colors,
+	#[cfg(feature = "images")]
+	registers: u32,
+	#[cfg(feature = "images")]
+	geometry:  Option<(u32, u32)>,
+	#[cfg(feature = "images")]
+	regis:     Option<Regis>,
+	soft:      Soft,
+
+	status:  Option<status::Line>,
+	writing: bool,
+	last:    Option<String>,
+
+	reordered: Vec<bool>,
+	printer:   Printer,
+	logger:    Logger,
+
 	cursor: Cursor,
 	saved:  Option<Cursor>,
 }
@@ -67,13 +95,25 @@ unsafe impl Send for Terminal { }
 #[derive(Debug)]
 enum Command {
 	Device,
+	#[cfg(feature = "images")]
 	Sixel(Sixel),
 	Internal,
+	Application,
+	#[cfg(feature = "images")]
+	Regis,
+	Soft(Vec<u32>),
+	Ignore,
 }
 
+/// The default and maximum number of sixel color registers.
+#[cfg(feature = "images")]
+const REGISTERS: u32     = 256;
+#[cfg(feature = "images")]
+const MAX_REGISTERS: u32 = 1024;
+
 macro_rules! term {
 	($term:ident; charset) => (
-		$term.cursor.charsets[$term.cursor.charset as usize]
+		$term.cursor.single.unwrap_or($term.cursor.charset) as usize
 	);
 
 	($term:ident; scroll! up $n:tt) => (
@@ -149,24 +189,53 @@ impl Terminal {
 	/// Create a new terminal.
 	pub fn new(config: Arc<Config>, font: (u32, u32), (width, height): (u32, u32)) -> error::Result<Self> {
 		let region = Region::from(0, 0, width, height);
-		let grid   = Grid::new(width, height, config.environment().scroll());
+		let grid   = Grid::new(width, height, config.environment().scroll(), config.environment().spill());
 		let tabs   = Tabs::new(width, height);
 
+		let mut mode = Mode::default();
+		if !config.environment().encoding().is_unicode() {
+			mode.remove(mode::UTF8);
+		}
+
 		Ok(Terminal {
 			config: config.clone(),
 			font:   font,
 
 			region:  region,
 			cache:   Default::default(),
-			touched: Touched::default(),
-			mode:    Mode::default(),
-			click:   None,
-			command: None,
+			touched:  Touched::default(),
+			mode:     mode,
+			encoding: config.environment().encoding(),
+			click:    None,
+			command:  None,
 
 			scroll: None,
 			grid:   grid,
 			tabs:   tabs,
 
+			#[cfg(feature = "images")]
+			images:   Images::default(),
+			#[cfg(feature = "images")]
+			graphics: None,
+
+			#[cfg(feature = "images")]
+			palette:   Default::default(),
+			#[cfg(feature = "images")]
+			registers: REGISTERS,
+			#[cfg(feature = "images")]
+			geometry:  None,
+			#[cfg(feature = "images")]
+			regis:     None,
+			soft:      Soft::default(),
+
+			status:  None,
+			writing: false,
+			last:    None,
+
+			reordered: vec![false; height as usize],
+			printer:   Printer::new(config.environment().printer()),
+			logger:    Logger::new(config.environment().log()),
+
 			cursor: Cursor::new(config.clone(), width, height),
 			saved:  None,
 		})
@@ -192,11 +261,79 @@ impl Terminal {
 		self.mode
 	}
 
+	/// Get the current encoding.
+	pub fn encoding(&self) -> Encoding {
+		self.encoding
+	}
+
+	/// Change the encoding.
+	pub fn set_encoding(&mut self, value: Encoding) {
+		self.encoding = value;
+
+		if value.is_unicode() {
+			self.mode.insert(mode::UTF8);
+		}
+		else {
+			self.mode.remove(mode::UTF8);
+		}
+	}
+
 	/// Get the internal grid.
 	pub fn grid(&self) -> &Grid {
 		&self.grid
 	}
 
+	/// Drop the scroll back and anything placed in it.
+	pub fn clear_history(&mut self) {
+		self.grid.clear_history();
+		self.clean();
+	}
+
+	/// Check if the session is being logged.
+	pub fn is_logging(&self) -> bool {
+		self.logger.is_enabled()
+	}
+
+	/// Turn session logging on or off, returns whether it's on.
+	pub fn toggle_logging(&mut self) -> bool {
+		let enabled = self.logger.toggle();
+		self.indicator();
+
+		enabled
+	}
+
+	/// Inflate the scroll back rows between the given lines so they can be
+	/// read.
+	pub fn inflate(&mut self, start: i64, end: i64) {
+		self.grid.inflate(start, end);
+	}
+
+	/// Drop any scroll back rows inflated for reading.
+	pub fn deflate(&mut self) {
+		self.grid.deflate();
+	}
+
+	/// Get the soft character sets.
+	pub fn soft(&self) -> &Soft {
+		&self.soft
+	}
+
+	/// Get the stored images.
+	#[cfg(feature = "images")]
+	pub fn images(&self) -> &Images {
+		&self.images
+	}
+
+	/// Get the images placed over the given cell.
+	#[cfg(feature = "images")]
+	pub fn placed(&self, x: u32, y: u32) -> Vec<image::Placed> {
+		if self.images.is_empty() {
+			return Vec::new();
+		}
+
+		self.images.at(x, y, self.grid.scrolled() + y as i64)
+	}
+
 	/// Get the cursor.
 	pub fn cursor(&self) -> cursor::Cell {
 		let (x, y) = term!(self; cursor);
@@ -205,7 +342,27 @@ impl Terminal {
 
 	/// Get the region of the terminal.
 	pub fn region(&self) -> Region {
-		self.region
+		if self.status.is_some() {
+			Region::from(0, 0, self.region.width, self.region.height + 1)
+		}
+		else {
+			self.region
+		}
+	}
+
+	/// Get the status line, it's drawn on the row after the last one.
+	pub fn status(&self) -> Option<&status::Line> {
+		self.status.as_ref()
+	}
+
+	/// Get the display order of the given row, if it's reordered.
+	pub fn bidi(&self, y: u32) -> Option<bidi::Map> {
+		if !self.mode.contains(mode::BIDI) || y >= self.region.height {
+			return None;
+		}
+
+		bidi::Map::new(self.region.width, self.mode.contains(mode::RIGHT_TO_LEFT),
+			|x| &self.grid[(x, y)])
 	}
 
 	/// Get an iterator over positioned cells.
@@ -215,10 +372,25 @@ impl Terminal {
 
 	/// Resize the terminal.
 	pub fn resize(&mut self, width: u32, height: u32) {
+		// The status line takes the last row, without room for it it's dropped.
+		if height <= 1 {
+			self.status  = None;
+			self.writing = false;
+		}
+
+		let height = if self.status.is_some() {
+			self.status = Some(self.line(self.status.as_ref().unwrap().is_host(), width));
+			height - 1
+		}
+		else {
+			height
+		};
+
 		self.region.width  = width;
 		self.region.height = height;
 
 		self.tabs.resize(width, height);
+		self.reordered.resize(height as usize, false);
 
 		match self.grid.resize(width, height) {
 			n if n > 0 => {
@@ -234,6 +406,15 @@ impl Terminal {
 
 		self.cursor.resize(width, height);
 		self.saved = None;
+
+		#[cfg(feature = "images")]
+		{
+			let font = self.font;
+
+			if let Some(regis) = self.regis.as_mut() {
+				regis.resize((width * font.0, height * font.1), font);
+			}
+		}
 	}
 
 	/// Enable or disable blinking and return the affected cells.
@@ -256,7 +437,8 @@ impl Terminal {
 			}
 		}
 
-		self.touched.iter(self.region)
+		let region = self.region();
+		self.touched.iter(region)
 	}
 
 	/// Send focus events.
@@ -279,7 +461,12 @@ impl Terminal {
 			try!(output.write_all(b"\x1B[200~"));
 		}
 
-		try!(output.write_all(value));
+		if self.encoding.is_unicode() {
+			try!(output.write_all(value));
+		}
+		else {
+			try!(output.write_all(&self.encoding.encode(&String::from_utf8_lossy(value))));
+		}
 
 		if self.mode.contains(mode::BRACKETED_PASTE) {
 			try!(output.write_all(b"\x1B[201~"));
@@ -346,11 +533,11 @@ impl Terminal {
 						output.write_all(&[0])
 					}
 					else {
-						output.write_all(string.as_bytes())
+						output.write_all(&self.encoding.encode(string))
 					}
 				}
 				else {
-					output.write_all(string.as_bytes())
+					output.write_all(&self.encoding.encode(string))
 				}
 			}
 
@@ -769,6 +956,8 @@ impl Terminal {
 		let     input  = input.as_ref();
 		let mut buffer = self.cache.take();
 
+		self.logger.raw(input);
+
 		if let Some(buffer) = buffer.as_mut() {
 			buffer.extend_from_slice(input);
 		}
@@ -780,6 +969,23 @@ impl Terminal {
 		debug!(target: "cancer::terminal::input::raw", "input: {:?}", input);
 
 		while !input.is_empty() {
+			// In printer controller mode the input goes to the printer untouched.
+			if self.printer.is_controlling() {
+				let (data, rest, end) = printer::split(input, self.eight_bit_input());
+				self.printer.write(data);
+
+				if end {
+					self.printer.stop();
+				}
+				else if !rest.is_empty() {
+					self.cache = Some(rest.to_vec());
+					break;
+				}
+
+				input = rest;
+				continue;
+			}
+
 			if let Some(command) = self.command.take() {
 				if !C1::is_string(input[0]) && C1::is_end(input).is_err() {
 					debug!(target: "cancer::terminal::input", "invalid string {:0x}", input[0]);
@@ -788,18 +994,55 @@ impl Terminal {
 
 				match command {
 					Command::Device => {
+						// Request status string.
+						if input.starts_with(b"$q") {
+							match C1::string(&input[2..]) {
+								control::Result::Done(rest, item) => {
+									input = rest;
+
+									let eight = self.eight_bit();
+									try!(self.request(item, Controls::new(output.by_ref(), eight)));
+									continue;
+								}
+
+								control::Result::Incomplete(..) => {
+									debug!(target: "cancer::terminal::input", "incomplete input: {:?}", input);
+									self.cache   = Some(input.to_vec());
+									self.command = Some(Command::Device);
+									break;
+								}
+
+								control::Result::Error(..) => ()
+							}
+						}
+
+						#[cfg(feature = "images")]
 						match DEC::SIXEL::header(input) {
 							control::Result::Done(rest, header) => {
 								debug!(target: "cancer::terminal::input::sixel", "sixel {:?}", header);
 
 								input = rest;
 
-								let origin = term!(self; cursor);
-								let sixel  = Sixel::new(origin, header,
+								// In sixel display mode images always start from the top left.
+								let origin = if self.mode.contains(mode::SIXEL_DISPLAY) {
+									(0, 0)
+								}
+								else {
+									term!(self; cursor)
+								};
+
+								let mut sixel = Sixel::new(origin, header,
 									self.cursor.style().background().unwrap_or(self.config.style().color().background()),
 									(self.font.0, self.font.1),
 									(origin.0, self.region.width));
 
+								sixel.registers(self.registers);
+								sixel.maximum(self.geometry());
+
+								if !self.mode.contains(mode::SIXEL_PRIVATE) {
+									sixel.shared(self.palette.clone());
+								}
+
 								self.command = Some(Command::Sixel(sixel));
 								continue;
 							}
@@ -814,9 +1057,96 @@ impl Terminal {
 							control::Result::Error(..) => ()
 						}
 
-						// TODO: insert ReGIS header check
+						if let Some((rest, params)) = soft::header(input) {
+							debug!(target: "cancer::terminal::input::soft", "soft font {:?}", params);
+
+							input        = rest;
+							self.command = Some(Command::Soft(params));
+							continue;
+						}
+
+						#[cfg(feature = "images")]
+						if let Some((rest, mode)) = regis::header(input) {
+							debug!(target: "cancer::terminal::input::regis", "regis mode {}", mode);
+
+							input = rest;
+
+							// Modes 1 and 3 start from a clear screen, the others resume
+							// drawing.
+							if mode == 1 || mode == 3 || self.regis.is_none() {
+								self.regis = Some(Regis::new((self.region.width * self.font.0, self.region.height * self.font.1)));
+							}
+
+							self.command = Some(Command::Regis);
+							continue;
+						}
+
+						// Unknown strings are dropped, and so are sixel and ReGIS ones
+						// without image support.
+						self.command = Some(Command::Ignore);
+						continue;
+					}
+
+					Command::Ignore => {
+						match C1::string(input) {
+							control::Result::Done(rest, _) => {
+								input = rest;
+							}
+
+							control::Result::Incomplete(..) => {
+								debug!(target: "cancer::terminal::input", "incomplete input: {:?}", input);
+								self.cache   = Some(input.to_vec());
+								self.command = Some(Command::Ignore);
+								break;
+							}
+
+							control::Result::Error(..) =>
+								break,
+						}
+					}
+
+					Command::Soft(params) => {
+						match C1::string(input) {
+							control::Result::Done(rest, item) => {
+								input = rest;
+								self.soft.load(&params, item);
+							}
+
+							control::Result::Incomplete(..) => {
+								debug!(target: "cancer::terminal::input", "incomplete input: {:?}", input);
+								self.cache   = Some(input.to_vec());
+								self.command = Some(Command::Soft(params));
+								break;
+							}
+
+							control::Result::Error(..) =>
+								break,
+						}
+					}
+
+					#[cfg(feature = "images")]
+					Command::Regis => {
+						match C1::string(input) {
+							control::Result::Done(rest, item) => {
+								input = rest;
+
+								let eight = self.eight_bit();
+								try!(self.regis(item, Controls::new(output.by_ref(), eight)));
+							}
+
+							control::Result::Incomplete(..) => {
+								debug!(target: "cancer::terminal::input", "incomplete input: {:?}", input);
+								self.cache   = Some(input.to_vec());
+								self.command = Some(Command::Regis);
+								break;
+							}
+
+							control::Result::Error(..) =>
+								break,
+						}
 					}
 
+					#[cfg(feature = "images")]
 					Command::Sixel(mut sixel) => {
 						match C1::is_end(input) {
 							// Move the drawn grid into the terminal.
@@ -825,9 +1155,32 @@ impl Terminal {
 
 								input = rest;
 
+								if !self.mode.contains(mode::SIXEL_PRIVATE) {
+									self.palette = sixel.colors().clone();
+								}
+
 								let rows = sixel.rows();
 								let edge = sixel.origin().0;
 
+								// In sixel display mode the image is drawn from the top left,
+								// it never scrolls and the cursor doesn't move.
+								if self.mode.contains(mode::SIXEL_DISPLAY) {
+									for (y, row) in sixel.into_inner().into_iter().enumerate().take(self.region.height as usize) {
+										let mut width = 0;
+
+										for (x, buffer) in row.into_iter().enumerate().take(self.region.width as usize) {
+											self.grid[(x as u32, y as u32)].make_image(buffer, self.cursor.style().clone());
+											self.touched.mark(x as u32, y as u32);
+											width += 1;
+										}
+
+										// Clean leftover references.
+										term!(self; clean references (width, y as u32));
+									}
+
+									continue;
+								}
+
 								// Move each row into the grid.
 								for (i, row) in sixel.into_inner().into_iter().enumerate() {
 									let (x, y) = term!(self; cursor);
@@ -841,8 +1194,9 @@ impl Terminal {
 									let (x, y) = term!(self; cursor);
 									self.grid.clean_references(x - 1, y);
 
-									// If it's the last row, skip cursor movement.
-									if i < rows - 1 {
+									// If it's the last row, the cursor only moves below the image
+									// unless it should stay to its right.
+									if i < rows - 1 || !self.mode.contains(mode::SIXEL_CURSOR) {
 										if term!(self; cursor Down(1)).is_some() {
 											term!(self; scroll! up 1);
 										}
@@ -904,15 +1258,83 @@ impl Terminal {
 								break,
 						}
 					}
+
+					Command::Application => {
+						match C1::string(input) {
+							control::Result::Done(rest, item) => {
+								input = rest;
+
+								if item.starts_with('G') {
+									let eight = self.eight_bit();
+									try!(self.graphics(&item[1..], Controls::new(output.by_ref(), eight)));
+								}
+							}
+
+							control::Result::Incomplete(..) => {
+								debug!(target: "cancer::terminal::input", "incomplete input: {:?}", input);
+								self.cache   = Some(input.to_vec());
+								self.command = Some(Command::Application);
+								break;
+							}
+
+							control::Result::Error(..) =>
+								break,
+						}
+					}
 				}
 
 				continue;
 			}
 
-			// Try to parse the input.
-			let item = match control::parse(input) {
+			// Locking shifts of G2 and G3 into GL are unknown to the parser.
+			if input.len() >= 2 && input[0] == 0x1B && (input[1] == b'n' || input[1] == b'o') {
+				debug!(target: "cancer::terminal::input::parsed", "locking shift: G{}", input[1] - b'n' + 2);
+
+				self.cursor.charset = input[1] - b'n' + 2;
+				input = &input[2 ..];
+				continue;
+			}
+
+			// Designations of soft character sets are unknown to the parser.
+			if let Some((rest, slot, font)) = self.soft.designate(input) {
+				debug!(target: "cancer::terminal::input::soft", "designate G{} to soft font {}", slot, font);
+
+				input = rest;
+				self.cursor.soft[slot] = Some(font);
+				continue;
+			}
+
+			// Try to parse the input, 8-bit C1 controls are only recognized if the
+			// encoding leaves room for them.
+			let parsed = if input[0] >= 0x80 && input[0] < 0xA0 && !self.eight_bit_input() {
+				None
+			}
+			else if let Some((rest, item)) = self.sequence(input) {
+				Some(control::Result::Done(rest, item))
+			}
+			else {
+				Some(control::parse(input))
+			};
+
+			let item = match parsed {
+				// No control code in a legacy encoding.
+				None | Some(control::Result::Error(_)) if !self.mode.contains(mode::UTF8) => {
+					let length = cmp::max(1, self.encoding.printable(input));
+					let mut buffer = [0u8; 4];
+
+					debug!(target: "cancer::terminal::input::parsed", "insert: {:?}", &input[.. length]);
+
+					for &byte in &input[.. length] {
+						let ch = self.encoding.decode(byte);
+						self.insert(&*ch.encode_utf8(&mut buffer));
+					}
+
+					input = &input[length ..];
+					continue;
+				}
+
 				// No control code.
-				control::Result::Error(_) => {
+				None | Some(control::Result::Error(_)) => {
 					let kind = match input::parse(input) {
 						// Invalid encoding.
 						Input::Error(0) => {
@@ -960,24 +1382,72 @@ impl Terminal {
 				}
 
 				// The given input isn't a complete sequence, cache it.
-				control::Result::Incomplete(_) => {
+				Some(control::Result::Incomplete(_)) => {
 					debug!(target: "cancer::terminal::input", "incomplete input: {:?}", input);
 					self.cache = Some(input.to_vec());
 					break;
 				}
 
 				// Parsed a control sequence.
-				control::Result::Done(rest, item) => {
+				Some(control::Result::Done(rest, item)) => {
 					input = rest;
 					item
 				}
 			};
 
 			debug!(target: "cancer::terminal::input::parsed", "item: {:?}", item);
-			actions.extend(self.control(item, output.by_ref())?);
+
+			let eight = self.eight_bit();
+			actions.extend(self.control(item, Controls::new(output.by_ref(), eight))?);
+		}
+
+		self.clean();
+
+		self.indicator();
+		self.reorder();
+
+		let region = self.region();
+		Ok((actions.into_iter(), self.touched.iter(region)))
+	}
+
+	/// Check if 8-bit C1 controls are accepted from the input.
+	fn eight_bit_input(&self) -> bool {
+		!self.mode.contains(mode::UTF8) && self.encoding.controls()
+	}
+
+	/// Parse the control sequences the parser rejects or mistakes for others,
+	/// they're passed on as unknown sequences.
+	fn sequence<'a>(&self, input: &'a [u8]) -> Option<(&'a [u8], Control)> {
+		let rest = if input.starts_with(b"\x1B[") {
+			&input[2 ..]
+		}
+		else if input[0] == 0x9B && self.eight_bit_input() {
+			&input[1 ..]
+		}
+		else {
+			return None;
+		};
+
+		let digits = rest.iter().take_while(|&&b| b >= b'0' && b <= b'9').count();
+		let value  = str::from_utf8(&rest[.. digits]).unwrap().parse::<u32>().ok();
+
+		match rest.get(digits) {
+			// ED 3, the parser only knows about 0 to 2.
+			Some(&b'J') if value == Some(3) => (),
+
+			// The ECMA-48 SD, the parser reads it as SIMD.
+			Some(&b'^') => (),
+
+			_ => return None,
 		}
 
-		Ok((actions.into_iter(), self.touched.iter(self.region)))
+		Some((&rest[digits + 1 ..], Control::C1(C1::ControlSequence(
+			CSI::Unknown(rest[digits], None, iter::once(value).collect())))))
+	}
+
+	/// Check if replies should use 8-bit C1 controls.
+	fn eight_bit(&self) -> bool {
+		self.mode.contains(mode::EIGHT_BIT) && self.eight_bit_input()
 	}
 
 	fn control<O: Write>(&mut self, control: Control, mut output: O) -> error::Result<Vec<Action>> {
@@ -986,7 +1456,7 @@ impl Terminal {
 		match control {
 			// Attributes.
 			Control::C1(C1::ControlSequence(CSI::DeviceAttributes(0))) => {
-				try!(output.write_all(b"\033[?64;6;21c"));
+				try!(output.write_all(b"\x1B[?64;6;21c"));
 			}
 
 			Control::C1(C1::ControlSequence(CSI::DeviceStatusReport(CSI::Report::CursorPosition))) => {
@@ -994,67 +1464,230 @@ impl Terminal {
 					&CSI::CursorPositionReport { x: self.cursor.x(), y: self.cursor.y() }));
 			}
 
-			Control::DEC(DEC::Unicode(value)) => {
-				if value {
-					self.mode.insert(mode::UTF8);
-				}
-				else {
-					self.mode.remove(mode::UTF8);
-				}
+			// DECXCPR
+			Control::C1(C1::ControlSequence(CSI::Private(b'n', None, ref args))) if args.get(0) == Some(&Some(6)) => {
+				try!(write!(output, "\x1B[?{};{};1R", self.cursor.y() + 1, self.cursor.x() + 1));
 			}
 
-			Control::DEC(DEC::ScrollRegion { top, bottom }) => {
-				let mut top    = top;
-				let mut bottom = bottom.unwrap_or(self.region.height);
+			// DECRQCRA
+			Control::C1(C1::ControlSequence(CSI::Unknown(b'y', Some(b'*'), ref args))) => {
+				let arg = |i: usize, default: u32| args.get(i).and_then(|v| *v).unwrap_or(default);
 
-				top    = util::clamp(top, 0, self.region.height - 1);
-				bottom = util::clamp(bottom, 0, self.region.height - 1);
+				// The rectangle is relative to the scrolling region in origin mode.
+				let offset = if self.cursor.state.contains(cursor::ORIGIN) { self.cursor.scroll.0 } else { 0 };
+				let top    = cmp::max(1, arg(2, 1)) - 1 + offset;
+				let left   = cmp::max(1, arg(3, 1)) - 1;
+				let bottom = cmp::min(self.region.height, arg(4, self.region.height) + offset);
+				let right  = cmp::min(self.region.width, arg(5, self.region.width));
 
-				if top > bottom {
-					mem::swap(&mut top, &mut bottom);
-				}
+				let mut sum = 0u32;
 
-				self.cursor.scroll = (top, bottom);
-				term!(self; cursor Position(Some(0), Some(0)));
-			}
+				for y in top .. bottom {
+					for x in left .. right {
+						let cell = &self.grid[(x, y)];
 
-			Control::C1(C1::ControlSequence(CSI::Set(modes))) => {
-				debug!(target: "cancer::terminal::mode::set", "set ECMA modes: {:?}", modes);
+						if cell.is_reference() {
+							continue;
+						}
 
-				for mode in modes {
-					match mode {
-						CSI::Mode::KeyboardLock =>
-							self.mode.insert(mode::KEYBOARD_LOCK),
+						let attributes = cell.style().attributes();
 
-						CSI::Mode::InsertionReplacement =>
-							self.mode.insert(mode::INSERT),
+						if attributes.contains(style::BOLD)      { sum = sum.wrapping_add(0x80) }
+						if attributes.contains(style::BLINK)     { sum = sum.wrapping_add(0x40) }
+						if attributes.contains(style::REVERSE)   { sum = sum.wrapping_add(0x20) }
+						if attributes.contains(style::UNDERLINE) { sum = sum.wrapping_add(0x10) }
+						if attributes.contains(style::INVISIBLE) { sum = sum.wrapping_add(0x08) }
 
-						CSI::Mode::SendReceive =>
-							self.mode.insert(mode::ECHO),
+						sum = sum.wrapping_add(if cell.is_occupied() {
+							cell.value().chars().next().map(|c| c as u32).unwrap_or(0x20)
+						}
+						else {
+							0x20
+						});
+					}
+				}
 
-						CSI::Mode::LineFeed =>
-							self.mode.insert(mode::CRLF),
+				try!(write!(output, "\x1BP{}!~{:04X}\x1B\\", arg(0, 0), sum.wrapping_neg() & 0xFFFF));
+			}
 
-						mode =>
-							debug!(target: "cancer::terminal::unhandled", "unhandled set: {:?}", mode)
+			// DECRQTSR
+			Control::C1(C1::ControlSequence(CSI::Unknown(b'u', Some(b'$'), ref args))) => {
+				match args.get(0).and_then(|v| *v).unwrap_or(0) {
+					// The terminal state is opaque, it's the current modes.
+					1 => {
+						try!(write!(output, "\x1BP1$s{:08X}\x1B\\", self.mode.bits()));
+					}
+
+					// The color table, in RGB percentages.
+					2 => {
+						let colors = (0 .. 16u8).map(|i| {
+							let color = self.config.color().get(i);
+
+							format!("{};2;{};{};{}", i,
+								(color.red * 100.0).round() as u8,
+								(color.green * 100.0).round() as u8,
+								(color.blue * 100.0).round() as u8)
+						}).collect::<Vec<_>>();
+
+						try!(write!(output, "\x1BP2$s{}\x1B\\", colors.join("/")));
 					}
+
+					_ => ()
 				}
 			}
 
-			Control::DEC(DEC::Set(modes)) => {
-				debug!(target: "cancer::terminal::mode::set", "set DEC modes: {:?}", modes);
+			// Tab stop report.
+			Control::C1(C1::ControlSequence(CSI::Unknown(b'w', Some(b'$'), ref args))) if args.get(0) == Some(&Some(2)) => {
+				let stops = (0 .. self.region.width).filter(|&x| self.tabs.get(x))
+					.map(|x| (x + 1).to_string()).collect::<Vec<_>>();
 
-				for mode in modes {
-					match mode {
-						DEC::Mode::ApplicationCursor =>
-							self.mode.insert(mode::APPLICATION_CURSOR),
+				try!(write!(output, "\x1BP2$u{}\x1B\\", stops.join("/")));
+			}
 
-						DEC::Mode::ReverseVideo => {
-							self.mode.insert(mode::REVERSE);
-							self.touched.all();
-						}
+			// Cursor information report.
+			Control::C1(C1::ControlSequence(CSI::Unknown(b'w', Some(b'$'), ref args))) if args.get(0) == Some(&Some(1)) => {
+				let mut rendition = 0x40;
+				let mut flags     = 0x40;
+				let mut extended  = 0x40;
+				let mut sets      = String::new();
 
-						DEC::Mode::Origin => {
+				{
+					let attributes = self.cursor.style().attributes();
+
+					if attributes.contains(style::BOLD) {
+						rendition |= 1;
+					}
+
+					if attributes.contains(style::UNDERLINE) {
+						rendition |= 2;
+					}
+
+					if attributes.contains(style::BLINK) {
+						rendition |= 4;
+					}
+
+					if attributes.contains(style::REVERSE) {
+						rendition |= 8;
+					}
+				}
+
+				if self.cursor.state.contains(cursor::ORIGIN) {
+					flags |= 1;
+				}
+
+				match self.cursor.single {
+					Some(2) => flags |= 2,
+					Some(3) => flags |= 4,
+					_       => (),
+				}
+
+				if self.cursor.wrap() {
+					flags |= 8;
+				}
+
+				for (i, &set) in self.cursor.charsets.iter().enumerate() {
+					let (name, wide) = if let Some(font) = self.cursor.soft[i] {
+						self.soft.designation(font)
+					}
+					else {
+						charset::designation(set)
+					};
+
+					if wide {
+						extended |= 1 << i;
+					}
+
+					sets.push_str(name);
+				}
+
+				try!(write!(output, "\x1BP1$u{};{};1;{};@;{};{};2;{};{}\x1B\\",
+					self.cursor.y() + 1, self.cursor.x() + 1,
+					rendition as u8 as char, flags as u8 as char,
+					self.cursor.charset, extended as u8 as char, sets));
+			}
+
+			Control::DEC(DEC::Unicode(value)) => {
+				if value {
+					self.set_encoding(Encoding::Utf8);
+				}
+				else {
+					let encoding = self.config.environment().encoding();
+
+					self.set_encoding(if encoding.is_unicode() {
+						Encoding::Latin1
+					}
+					else {
+						encoding
+					});
+				}
+			}
+
+			Control::DEC(DEC::SevenBits) => {
+				self.mode.remove(mode::EIGHT_BIT);
+			}
+
+			Control::DEC(DEC::EightBits) => {
+				self.mode.insert(mode::EIGHT_BIT);
+			}
+
+			Control::DEC(DEC::ScrollRegion { top, bottom }) => {
+				let mut top    = top;
+				let mut bottom = bottom.unwrap_or(self.region.height);
+
+				top    = util::clamp(top, 0, self.region.height - 1);
+				bottom = util::clamp(bottom, 0, self.region.height - 1);
+
+				if top > bottom {
+					mem::swap(&mut top, &mut bottom);
+				}
+
+				self.cursor.scroll = (top, bottom);
+				term!(self; cursor Position(Some(0), Some(0)));
+			}
+
+			Control::C1(C1::ControlSequence(CSI::Set(modes))) => {
+				debug!(target: "cancer::terminal::mode::set", "set ECMA modes: {:?}", modes);
+
+				for mode in modes {
+					match mode {
+						CSI::Mode::KeyboardLock =>
+							self.mode.insert(mode::KEYBOARD_LOCK),
+
+						CSI::Mode::InsertionReplacement =>
+							self.mode.insert(mode::INSERT),
+
+						CSI::Mode::SendReceive =>
+							self.mode.insert(mode::ECHO),
+
+						CSI::Mode::LineFeed =>
+							self.mode.insert(mode::CRLF),
+
+						// BDSM, the application handles the reordering itself.
+						CSI::Mode::BidirectionalSupport => {
+							self.mode.remove(mode::BIDI);
+							self.touched.all();
+						}
+
+						mode =>
+							debug!(target: "cancer::terminal::unhandled", "unhandled set: {:?}", mode)
+					}
+				}
+			}
+
+			Control::DEC(DEC::Set(modes)) => {
+				debug!(target: "cancer::terminal::mode::set", "set DEC modes: {:?}", modes);
+
+				for mode in modes {
+					match mode {
+						DEC::Mode::ApplicationCursor =>
+							self.mode.insert(mode::APPLICATION_CURSOR),
+
+						DEC::Mode::ReverseVideo => {
+							self.mode.insert(mode::REVERSE);
+							self.touched.all();
+						}
+
+						DEC::Mode::Origin => {
 							self.cursor.state.insert(cursor::ORIGIN);
 							term!(self; cursor Position(Some(0), Some(0)));
 						}
@@ -1067,8 +1700,11 @@ impl Terminal {
 							self.touched.push(term!(self; cursor));
 						}
 
-						DEC::Mode::SmallFont =>
-							actions.push(Action::Resize(132, 24)),
+						DEC::Mode::SmallFont => {
+							if self.mode.contains(mode::COLUMNS) {
+								actions.push(self.set_columns(132));
+							}
+						}
 
 						mode =>
 							debug!(target: "cancer::terminal::unhandled", "unhandled set: {:?}", mode)
@@ -1076,6 +1712,48 @@ impl Terminal {
 				}
 			}
 
+			// XTSMGRAPHICS
+			#[cfg(feature = "images")]
+			Control::C1(C1::ControlSequence(CSI::Private(b'S', None, args))) => {
+				let item   = args.get(0).and_then(|v| *v).unwrap_or(0);
+				let action = args.get(1).and_then(|v| *v).unwrap_or(0);
+				let value  = (args.get(2).and_then(|v| *v), args.get(3).and_then(|v| *v));
+				let limit  = (self.region.width * self.font.0, self.region.height * self.font.1);
+
+				let reply = match (item, action) {
+					(1, 1) | (1, 2) | (1, 3) | (1, 4) => {
+						match action {
+							2 => self.registers = REGISTERS,
+							3 => self.registers = cmp::max(1, cmp::min(MAX_REGISTERS, value.0.unwrap_or(REGISTERS))),
+							_ => (),
+						}
+
+						format!("0;{}", if action == 4 { MAX_REGISTERS } else { self.registers })
+					}
+
+					(2, 1) | (2, 2) | (2, 3) | (2, 4) => {
+						match action {
+							2 => self.geometry = None,
+							3 => self.geometry = Some((
+								cmp::min(limit.0, value.0.unwrap_or(limit.0)),
+								cmp::min(limit.1, value.1.unwrap_or(limit.1)))),
+							_ => (),
+						}
+
+						let (width, height) = if action == 4 { limit } else { self.geometry() };
+						format!("0;{};{}", width, height)
+					}
+
+					(1, _) | (2, _) =>
+						"2;0".into(),
+
+					_ =>
+						"1;0".into(),
+				};
+
+				try!(write!(output, "\x1B[?{};{}S", item, reply));
+			}
+
 			Control::C1(C1::ControlSequence(CSI::Private(b'h', None, args))) => {
 				debug!(target: "cancer::terminal::mode::set", "set private modes: {:?}", args);
 
@@ -1101,6 +1779,24 @@ impl Terminal {
 						1006 =>
 							self.mode.insert(mode::MOUSE_SGR),
 
+						80 =>
+							self.mode.insert(mode::SIXEL_DISPLAY),
+
+						1070 =>
+							self.mode.insert(mode::SIXEL_PRIVATE),
+
+						8452 =>
+							self.mode.insert(mode::SIXEL_CURSOR),
+
+						40 =>
+							self.mode.insert(mode::COLUMNS),
+
+						45 =>
+							self.mode.insert(mode::REVERSE_WRAP),
+
+						1045 =>
+							self.mode.insert(mode::EXTENDED_WRAP),
+
 						n =>
 							debug!(target: "cancer::terminal::unhandled", "unhandled set: {}", n)
 					}
@@ -1124,6 +1820,12 @@ impl Terminal {
 						CSI::Mode::LineFeed =>
 							self.mode.remove(mode::CRLF),
 
+						// BDSM, back to implicit reordering.
+						CSI::Mode::BidirectionalSupport => {
+							self.mode.insert(mode::BIDI);
+							self.touched.all();
+						}
+
 						mode =>
 							debug!(target: "cancer::terminal::unhandled", "unhandled reset: {:?}", mode)
 					}
@@ -1156,8 +1858,11 @@ impl Terminal {
 							self.touched.push(term!(self; cursor));
 						}
 
-						DEC::Mode::SmallFont =>
-							actions.push(Action::Resize(80, 24)),
+						DEC::Mode::SmallFont => {
+							if self.mode.contains(mode::COLUMNS) {
+								actions.push(self.set_columns(80));
+							}
+						}
 
 						mode =>
 							debug!(target: "cancer::terminal::unhandled", "unhandled reset: {:?}", mode)
@@ -1182,6 +1887,24 @@ impl Terminal {
 						1006 =>
 							self.mode.remove(mode::MOUSE_SGR),
 
+						80 =>
+							self.mode.remove(mode::SIXEL_DISPLAY),
+
+						1070 =>
+							self.mode.remove(mode::SIXEL_PRIVATE),
+
+						8452 =>
+							self.mode.remove(mode::SIXEL_CURSOR),
+
+						40 =>
+							self.mode.remove(mode::COLUMNS),
+
+						45 =>
+							self.mode.remove(mode::REVERSE_WRAP),
+
+						1045 =>
+							self.mode.remove(mode::EXTENDED_WRAP),
+
 						n =>
 							debug!(target: "cancer::terminal::unhandled", "unhandled reset: {:?}", n)
 					}
@@ -1210,10 +1933,95 @@ impl Terminal {
 				}
 			}
 
+			// DECSSDT
+			Control::C1(C1::ControlSequence(CSI::Unknown(b'~', Some(b'$'), ref args))) => {
+				let line = match args.get(0).and_then(|v| *v).unwrap_or(0) {
+					1 => Some(self.line(false, self.region.width)),
+					2 => Some(self.line(true, self.region.width)),
+					_ => None,
+				};
+
+				actions.extend(self.set_status(line));
+			}
+
+			// DECSASD
+			Control::C1(C1::ControlSequence(CSI::Unknown(b'}', Some(b'$'), ref args))) => {
+				self.writing = args.get(0).and_then(|v| *v) == Some(1) &&
+					self.status.as_ref().map(|s| s.is_host()).unwrap_or(false);
+			}
+
+			// Horizontal movement and line erasure go to the status line.
+			Control::C0(C0::CarriageReturn) |
+			Control::C0(C0::Backspace) |
+			Control::C1(C1::ControlSequence(CSI::CursorBack(..))) |
+			Control::C1(C1::ControlSequence(CSI::CursorForward(..))) |
+			Control::C1(C1::ControlSequence(CSI::CursorHorizontalPosition(..))) |
+			Control::C1(C1::ControlSequence(CSI::EraseLine(..))) if self.writing => {
+				let style = self.cursor.style().clone();
+
+				if let Some(status::Line::Host(ref mut host)) = self.status {
+					let x = host.x();
+
+					match control {
+						Control::C0(C0::CarriageReturn) => {
+							host.position(0);
+						}
+
+						Control::C0(C0::Backspace) => {
+							host.position(x.saturating_sub(1));
+						}
+
+						Control::C1(C1::ControlSequence(CSI::CursorBack(n))) => {
+							host.position(x.saturating_sub(n));
+						}
+
+						Control::C1(C1::ControlSequence(CSI::CursorForward(n))) => {
+							host.position(x.saturating_add(n));
+						}
+
+						Control::C1(C1::ControlSequence(CSI::CursorHorizontalPosition(n))) => {
+							host.position(n);
+						}
+
+						_ => {
+							host.erase(style);
+						}
+					}
+				}
+
+				self.touched.line(self.region.height);
+			}
+
+			// Anything moving between lines or touching the screen is meaningless
+			// on the status line, so it's dropped instead of hitting the screen.
+			Control::C0(C0::LineFeed) |
+			Control::C0(C0::HorizontalTabulation) |
+			Control::C1(C1::Index) |
+			Control::C1(C1::NextLine) |
+			Control::C1(C1::ReverseIndex) |
+			Control::DEC(DEC::BackIndex) |
+			Control::DEC(DEC::ForwardIndex) |
+			Control::C1(C1::ControlSequence(CSI::CursorPosition { .. })) |
+			Control::C1(C1::ControlSequence(CSI::CursorVerticalPosition(..))) |
+			Control::C1(C1::ControlSequence(CSI::CursorNextLine(..))) |
+			Control::C1(C1::ControlSequence(CSI::CursorPreviousLine(..))) |
+			Control::C1(C1::ControlSequence(CSI::CursorUp(..))) |
+			Control::C1(C1::ControlSequence(CSI::CursorDown(..))) |
+			Control::C1(C1::ControlSequence(CSI::CursorForwardTabulation(..))) |
+			Control::C1(C1::ControlSequence(CSI::CursorBackTabulation(..))) |
+			Control::C1(C1::ControlSequence(CSI::EraseDisplay(..))) |
+			Control::C1(C1::ControlSequence(CSI::InsertLine(..))) |
+			Control::C1(C1::ControlSequence(CSI::DeleteLine(..))) |
+			Control::C1(C1::ControlSequence(CSI::ScrollUp(..))) |
+			Control::C1(C1::ControlSequence(CSI::ScrollDown(..))) if self.writing => {
+				debug!(target: "cancer::terminal::status", "ignored on the status line: {:?}", control);
+			}
+
 			// Charset.
 			Control::DEC(DEC::SelectCharset(i, charset)) => {
-				if self.cursor.charsets.len() >= i as usize {
+				if self.cursor.charsets.len() > i as usize {
 					self.cursor.charsets[i as usize] = charset;
+					self.cursor.soft[i as usize]     = None;
 				}
 			}
 
@@ -1225,19 +2033,37 @@ impl Terminal {
 				self.cursor.charset = 1;
 			}
 
+			Control::C1(C1::SingleShiftTwo) => {
+				self.cursor.single = Some(2);
+			}
+
+			Control::C1(C1::SingleShiftThree) => {
+				self.cursor.single = Some(3);
+			}
+
 			// Movement functions.
 			Control::C0(C0::CarriageReturn) => {
 				term!(self; cursor Position(Some(0), None));
 			}
 
 			Control::C0(C0::LineFeed) => {
+				if self.mode.contains(mode::AUTO_PRINT) {
+					let line = self.text(self.cursor.y());
+					self.printer.print(line.as_bytes());
+				}
+
+				if self.logger.is_enabled() {
+					let line = self.paragraph(self.cursor.y());
+					self.logger.line(&line);
+				}
+
 				if term!(self; cursor Down(1)).is_some() {
 					term!(self; scroll! up 1);
 				}
 			}
 
 			Control::C0(C0::Backspace) => {
-				term!(self; cursor Left(1));
+				self.back(1);
 			}
 
 			Control::C1(C1::ControlSequence(CSI::CursorPosition { x, y })) => {
@@ -1252,6 +2078,16 @@ impl Terminal {
 				term!(self; cursor Position(Some(n), None));
 			}
 
+			Control::C1(C1::ControlSequence(CSI::CursorNextLine(n))) => {
+				term!(self; cursor Down(n));
+				term!(self; cursor Position(Some(0), None));
+			}
+
+			Control::C1(C1::ControlSequence(CSI::CursorPreviousLine(n))) => {
+				term!(self; cursor Up(n));
+				term!(self; cursor Position(Some(0), None));
+			}
+
 			Control::C1(C1::ControlSequence(CSI::CursorUp(n))) => {
 				term!(self; cursor Up(n));
 			}
@@ -1261,7 +2097,7 @@ impl Terminal {
 			}
 
 			Control::C1(C1::ControlSequence(CSI::CursorBack(n))) => {
-				term!(self; cursor Left(n));
+				self.back(n);
 			}
 
 			Control::C1(C1::ControlSequence(CSI::CursorForward(n))) => {
@@ -1288,6 +2124,87 @@ impl Terminal {
 				term!(self; scroll down n);
 			}
 
+			// The original ECMA-48 SD, still sent by some applications.
+			Control::C1(C1::ControlSequence(CSI::Unknown(b'^', None, ref args))) => {
+				let n = args.get(0).and_then(|v| *v).unwrap_or(1);
+				term!(self; scroll down n);
+			}
+
+			// SL, the contents move left as the view pans right.
+			Control::C1(C1::ControlSequence(CSI::ScrollLeft(n))) => {
+				self.grid.right(cmp::min(n, self.region.width));
+				self.touched.all();
+			}
+
+			// SR, the contents move right as the view pans left.
+			Control::C1(C1::ControlSequence(CSI::ScrollRight(n))) => {
+				self.grid.left(cmp::min(n, self.region.width));
+				self.touched.all();
+			}
+
+			// SCP, the parser doesn't know it, 1 is left to right and 2 right to left.
+			Control::C1(C1::ControlSequence(CSI::Unknown(b'k', Some(b' '), ref args))) => {
+				if args.get(0) == Some(&Some(2)) {
+					self.mode.insert(mode::RIGHT_TO_LEFT);
+				}
+				else {
+					self.mode.remove(mode::RIGHT_TO_LEFT);
+				}
+
+				self.touched.all();
+			}
+
+			// Print screen.
+			Control::C1(C1::ControlSequence(CSI::MediaCopy(CSI::Copy::ToPrimary))) => {
+				let screen = (0 .. self.region.height).map(|y| self.text(y)).collect::<String>();
+				self.printer.print(screen.as_bytes());
+			}
+
+			// Printer controller mode.
+			Control::C1(C1::ControlSequence(CSI::MediaCopy(CSI::Copy::StartPrimary))) => {
+				self.printer.start();
+			}
+
+			Control::C1(C1::ControlSequence(CSI::MediaCopy(CSI::Copy::StopPrimary))) => {
+				self.printer.stop();
+			}
+
+			Control::C1(C1::ControlSequence(CSI::Private(b'i', None, args))) => {
+				match args.get(0).and_then(|v| *v).unwrap_or(0) {
+					// Print the cursor line.
+					1 => {
+						let line = self.text(self.cursor.y());
+						self.printer.print(line.as_bytes());
+					}
+
+					// Auto print mode.
+					4 => {
+						self.mode.remove(mode::AUTO_PRINT);
+						self.printer.auto(false);
+					}
+
+					5 => {
+						self.mode.insert(mode::AUTO_PRINT);
+						self.printer.auto(true);
+					}
+
+					n =>
+						debug!(target: "cancer::terminal::unhandled", "unhandled media copy: {}", n)
+				}
+			}
+
+			Control::C1(C1::ControlSequence(CSI::Repeat(n))) => {
+				if let Some(last) = self.last.clone() {
+					for _ in 0 .. cmp::min(n, self.region.width * self.region.height) {
+						self.put(&last);
+					}
+				}
+			}
+
+			Control::C0(C0::Enquiry) => {
+				try!(output.write_all(self.config.environment().answerback().as_bytes()));
+			}
+
 			Control::DEC(DEC::BackIndex) => {
 				if self.cursor.x() == 0 {
 					self.grid.left(1);
@@ -1372,9 +2289,20 @@ impl Terminal {
 					self.grid.wrapped(y, false);
 				}
 
+				#[cfg(feature = "images")]
+				{
+					let (top, bottom) = (self.grid.scrolled(), self.grid.scrolled() + self.region.height as i64);
+					self.images.retain(|p| p.line + p.rows as i64 <= top || p.line >= bottom, false);
+				}
+
 				self.touched.all();
 			}
 
+			// Erase saved lines.
+			Control::C1(C1::ControlSequence(CSI::Unknown(b'J', None, ref args))) if args.get(0) == Some(&Some(3)) => {
+				self.clear_history();
+			}
+
 			Control::C1(C1::ControlSequence(CSI::EraseLine(CSI::Erase::ToEnd))) => {
 				let (mut x, y) = term!(self; cursor);
 
@@ -1690,9 +2618,12 @@ impl Terminal {
 				self.command = Some(Command::Device);
 			}
 
+			Control::C1(C1::ApplicationProgramCommand) => {
+				self.command = Some(Command::Application);
+			}
+
 			Control::C1(C1::String) |
 			Control::C1(C1::PrivacyMessage) |
-			Control::C1(C1::ApplicationProgramCommand) |
 			Control::C1(C1::SingleCharacter) |
 			Control::C1(C1::OperatingSystemCommand) => {
 				self.command = Some(Command::Internal);
@@ -1706,56 +2637,85 @@ impl Terminal {
 		Ok(actions)
 	}
 
+	/// Move the cursor back, going into the previous lines if reverse
+	/// wraparound allows it.
+	fn back(&mut self, mut n: u32) {
+		let (top, bottom) = self.cursor.scroll;
+		let last          = self.region.width - 1;
+
+		// Going around the whole scroll region ends up in the same place.
+		if self.mode.contains(mode::EXTENDED_WRAP) {
+			n %= self.region.width * (bottom - top + 1);
+		}
+
+		while n > 0 {
+			let (x, y)  = (self.cursor.x(), self.cursor.y());
+			let reverse = x == 0 && !self.cursor.wrap() && self.mode.contains(mode::WRAP);
+
+			// Go back into the previous line if reverse wraparound allows it.
+			if reverse && y > top && (self.mode.contains(mode::EXTENDED_WRAP) ||
+			   (self.mode.contains(mode::REVERSE_WRAP) && self.grid[y].is_wrapped()))
+			{
+				term!(self; cursor Up(1));
+				term!(self; cursor Position(Some(last), None));
+				n -= 1;
+			}
+			// Extended reverse wraparound goes from the top to the bottom.
+			else if reverse && y == top && self.mode.contains(mode::EXTENDED_WRAP) {
+				term!(self; cursor Down(bottom - top));
+				term!(self; cursor Position(Some(last), None));
+				n -= 1;
+			}
+			else if x > 0 {
+				let step = cmp::min(n, x);
+				term!(self; cursor Left(step));
+				n -= step;
+			}
+			else {
+				term!(self; cursor Left(n));
+				break;
+			}
+		}
+	}
+
 	fn insert<T: AsRef<str>>(&mut self, ch: T) {
-		let mut ch = ch.as_ref();
-
-		// Convert from normal characters to graphical characters if the charset is
-		// enabled.
-		if term!(self; charset) == DEC::Charset::DEC(DEC::charset::DEC::Graphic) {
-			ch = match ch {
-				"A" => "↑",
-				"B" => "↓",
-				"C" => "→",
-				"D" => "←",
-				"E" => "█",
-				"F" => "▚",
-				"G" => "☃",
-				"_" => " ",
-				"`" => "◆",
-				"a" => "▒",
-				"b" => "␉",
-				"c" => "␌",
-				"d" => "␍",
-				"e" => "␊",
-				"f" => "°",
-				"g" => "±",
-				"h" => "␤",
-				"i" => "␋",
-				"j" => "┘",
-				"k" => "┐",
-				"l" => "┌",
-				"m" => "└",
-				"n" => "┼",
-				"o" => "⎺",
-				"p" => "⎻",
-				"q" => "─",
-				"r" => "⎼",
-				"s" => "⎽",
-				"t" => "├",
-				"u" => "┤",
-				"v" => "┴",
-				"w" => "┬",
-				"x" => "│",
-				"y" => "≤",
-				"z" => "≥",
-				"{" => "π",
-				"|" => "≠",
-				"}" => "£",
-				"~" => "·",
-				_   => ch,
-			};
+		let mut buffer = [0u8; 4];
+		let mut ch     = ch.as_ref();
+
+		// Output redirected to the status line.
+		if self.writing {
+			let style = self.cursor.style().clone();
+
+			if let Some(status::Line::Host(ref mut host)) = self.status {
+				// Wide characters can affect the cells around them.
+				self.touched.line(self.region.height);
+				host.insert(ch, style);
+			}
+
+			return;
+		}
+
+		// Convert the character through the designated charset, a single shift
+		// only lasts for this character.
+		let slot = term!(self; charset);
+		self.cursor.single.take();
+
+		if ch.len() == 1 {
+			let byte = ch.as_bytes()[0] as char;
+
+			ch = &*if let Some(font) = self.cursor.soft[slot] {
+				self.soft.translate(font, byte)
+			}
+			else {
+				charset::translate(self.cursor.charsets[slot], byte)
+			}.encode_utf8(&mut buffer);
 		}
 
+		self.put(ch);
+	}
+
+	/// Put an already translated grapheme at the cursor.
+	fn put(&mut self, ch: &str) {
 		let width = ch.width() as u32;
 
 		// Bail out if it cannot be displayed.
@@ -1763,6 +2723,9 @@ impl Terminal {
 			return;
 		}
 
+		// Keep track of it for REP.
+		self.last = Some(ch.into());
+
 		// Wrap to the next line if needed.
 		if self.mode.contains(mode::WRAP) && self.cursor.wrap() {
 			if term!(self; cursor Down(1)).is_some() {
@@ -1813,6 +2776,555 @@ impl Terminal {
 		}
 	}
 
+	/// Create a status line of the given kind.
+	fn line(&self, host: bool, width: u32) -> status::Line {
+		if host {
+			status::Line::host(width)
+		}
+		else {
+			status::Line::indicator(self.config.overlay().status().cloned().unwrap_or_default(), width)
+		}
+	}
+
+	/// Change the status line, giving up or taking back the last row.
+	fn set_status(&mut self, line: Option<status::Line>) -> Option<Action> {
+		let before = self.status.is_some();
+		let height = self.region().height;
+
+		self.status  = line;
+		self.writing = false;
+		self.touched.all();
+
+		if before == self.status.is_some() {
+			return None;
+		}
+
+		let width = self.region.width;
+		self.resize(width, height);
+
+		Some(Action::Tty(self.region.width, self.region.height))
+	}
+
+	/// Switch the amount of columns, clearing the screen and margins.
+	fn set_columns(&mut self, columns: u32) -> Action {
+		for y in 0 .. self.region.height {
+			for x in 0 .. self.region.width {
+				self.grid[(x, y)].make_empty(self.cursor.style().clone());
+			}
+
+			self.grid.wrapped(y, false);
+		}
+
+		#[cfg(feature = "images")]
+		{
+			let (top, bottom) = (self.grid.scrolled(), self.grid.scrolled() + self.region.height as i64);
+			self.images.retain(|p| p.line + p.rows as i64 <= top || p.line >= bottom, false);
+		}
+
+		self.cursor.scroll = (0, self.region.height - 1);
+		term!(self; cursor Position(Some(0), Some(0)));
+		self.touched.all();
+
+		Action::Resize(columns, self.region().height)
+	}
+
+	/// Get the text of the given line for printing.
+	fn text(&self, y: u32) -> String {
+		let mut line = String::new();
+
+		for x in 0 .. self.region.width {
+			line.push_str(self.grid[(x, y)].value());
+		}
+
+		let mut line = line.trim_right().to_owned();
+		line.push('\n');
+		line
+	}
+
+	/// Get the text of the line ending at the given row, joining the rows
+	/// that wrapped into it.
+	fn paragraph(&self, y: u32) -> String {
+		let mut top = y;
+		while top > 0 && self.grid[top].is_wrapped() {
+			top -= 1;
+		}
+
+		let mut line = String::new();
+		for y in top ..= y {
+			for x in 0 .. self.region.width {
+				line.push_str(self.grid[(x, y)].value());
+			}
+		}
+
+		let mut line = line.trim_right().to_owned();
+		line.push('\n');
+		line
+	}
+
+	/// Touch the whole lines whose display order may have changed.
+	fn reorder(&mut self) {
+		if !self.mode.contains(mode::BIDI) {
+			return;
+		}
+
+		for y in self.touched.lines(self.region.height) {
+			let reordered = self.bidi(y).is_some();
+
+			if reordered || self.reordered[y as usize] {
+				self.touched.line(y);
+			}
+
+			self.reordered[y as usize] = reordered;
+		}
+	}
+
+	/// Update the indicator status line.
+	fn indicator(&mut self) {
+		let (x, y) = (self.cursor.x(), self.cursor.y());
+		let mut modes = Vec::new();
+
+		if self.mode.contains(mode::INSERT) {
+			modes.push("INSERT");
+		}
+
+		if self.mode.contains(mode::KEYBOARD_LOCK) {
+			modes.push("LOCKED");
+		}
+
+		if self.mode.contains(mode::APPLICATION_KEYPAD) {
+			modes.push("KEYPAD");
+		}
+
+		if self.cursor.state.contains(cursor::ORIGIN) {
+			modes.push("ORIGIN");
+		}
+
+		if self.logger.is_enabled() {
+			modes.push("LOG");
+		}
+
+		if let Some(status::Line::Indicator(ref mut status)) = self.status {
+			status.mode(modes.join(" "));
+			status.position((x + 1, y + 1));
+			self.touched.line(self.region.height);
+		}
+	}
+
+	/// Run ReGIS commands and attach the drawing to the grid.
+	#[cfg(feature = "images")]
+	fn regis<O: Write>(&mut self, string: &str, mut output: O) -> io::Result<()> {
+		let (reports, cells) = {
+			let regis   = try!(ok self.regis.as_mut().ok_or(()));
+			let reports = regis.handle(string);
+
+			(reports, regis.cells(self.font))
+		};
+
+		for report in reports {
+			try!(output.write_all(report.as_bytes()));
+		}
+
+		for ((x, y), image) in cells {
+			if x >= self.region.width || y >= self.region.height {
+				continue;
+			}
+
+			if let Some(image) = image {
+				self.grid[(x, y)].make_image(image, self.cursor.style().clone());
+			}
+			else {
+				self.grid[(x, y)].make_empty(self.cursor.style().clone());
+			}
+
+			self.touched.mark(x, y);
+		}
+
+		Ok(())
+	}
+
+	/// Display an iTerm2 inline image at the cursor.
+	#[cfg(feature = "images")]
+	fn inline(&mut self, file: iterm::File) {
+		debug!(target: "cancer::terminal::iterm", "file {:?} ({} bytes)", file.name, file.data.len());
+
+		// Files that aren't inline would be downloads, which we don't do.
+		if !file.inline {
+			return;
+		}
+
+		let (width, height, pixels) = try!(return option image::decode(&file.data));
+		let (cw, ch)                = self.font;
+
+		if width == 0 || height == 0 {
+			return;
+		}
+
+		let (width, height) = (width as u64, height as u64);
+		let size = match (file.width.pixels(cw, self.region.width), file.height.pixels(ch, self.region.height)) {
+			// Use the native size.
+			(None, None) =>
+				(width, height),
+
+			(Some(w), None) =>
+				(w as u64, height * w as u64 / width),
+
+			(None, Some(h)) =>
+				(width * h as u64 / height, h as u64),
+
+			// Fit the image within the requested box.
+			(Some(w), Some(h)) if file.preserve => {
+				if w as u64 * height <= h as u64 * width {
+					(w as u64, height * w as u64 / width)
+				}
+				else {
+					(width * h as u64 / height, h as u64)
+				}
+			}
+
+			(Some(w), Some(h)) =>
+				(w as u64, h as u64),
+		};
+
+		// Never go beyond the screen, shrinking the image if it's bigger.
+		let limit = (cmp::min(self.region.width * cw, image::MAXIMUM) as u64, cmp::min(self.region.height * ch, image::MAXIMUM) as u64);
+		let size  = (cmp::min(size.0, u32::max_value() as u64), cmp::min(size.1, u32::max_value() as u64));
+		let size  = if size.0 <= limit.0 && size.1 <= limit.1 {
+			size
+		}
+		else if size.0 * limit.1 >= size.1 * limit.0 {
+			(limit.0, size.1 * limit.0 / size.0)
+		}
+		else {
+			(size.0 * limit.1 / size.1, limit.1)
+		};
+
+		let (width, height) = (width as u32, height as u32);
+		let size            = (size.0 as u32, size.1 as u32);
+
+		if size.0 == 0 || size.1 == 0 {
+			return;
+		}
+
+		let origin = term!(self; cursor);
+		let edge   = origin.0;
+		let grid   = image::slice(&pixels, (width, height), size, (cw, ch), self.region.width - origin.0);
+		let rows   = grid.len();
+
+		// Move each row into the grid, the same way sixels are.
+		for (i, row) in grid.into_iter().enumerate() {
+			let (x, y) = term!(self; cursor);
+
+			for (offset, buffer) in row.into_iter().enumerate() {
+				self.grid[(x + offset as u32, y)].make_image(buffer, self.cursor.style().clone());
+				term!(self; cursor Right(1));
+			}
+
+			// Clean leftover references.
+			let (x, y) = term!(self; cursor);
+			self.grid.clean_references(x - 1, y);
+
+			// If it's the last row, skip cursor movement.
+			if i < rows - 1 {
+				if term!(self; cursor Down(1)).is_some() {
+					term!(self; scroll! up 1);
+				}
+
+				term!(self; cursor Position(Some(edge), None));
+			}
+		}
+	}
+
+	/// Get the maximum size in pixels of sixel images.
+	#[cfg(feature = "images")]
+	fn geometry(&self) -> (u32, u32) {
+		self.geometry.unwrap_or((self.region.width * self.font.0, self.region.height * self.font.1))
+	}
+
+	/// Drop any placement that went beyond the scroll back.
+	#[cfg(feature = "images")]
+	fn clean(&mut self) {
+		if !self.images.is_empty() {
+			self.images.clean(self.grid.oldest());
+		}
+	}
+
+	#[cfg(not(feature = "images"))]
+	fn clean(&mut self) { }
+
+	/// Without image support graphics requests are dropped.
+	#[cfg(not(feature = "images"))]
+	fn graphics<O: Write>(&mut self, _string: &str, _output: O) -> io::Result<()> {
+		Ok(())
+	}
+
+	/// Handle a kitty graphics protocol request.
+	#[cfg(feature = "images")]
+	fn graphics<O: Write>(&mut self, string: &str, output: O) -> io::Result<()> {
+		let mut request = try!(ok kitty::parse(string).ok_or(()));
+		debug!(target: "cancer::terminal::kitty", "request: {:?}", request.action as char);
+
+		// Collect chunked transmissions, too much data drops the whole request.
+		if let Some(mut pending) = self.graphics.take() {
+			if let Err(err) = pending.chunk(request) {
+				return self.answer(&pending, Err(err), output);
+			}
+
+			if pending.more {
+				self.graphics = Some(pending);
+				return Ok(());
+			}
+
+			request = pending;
+		}
+		else if request.more {
+			self.graphics = Some(request);
+			return Ok(());
+		}
+
+		let result = match request.action {
+			b't' | b'T' | b'q' => {
+				match request.load() {
+					Ok(_) if request.action == b'q' =>
+						Ok(request.id),
+
+					Ok(image) => {
+						let id = self.images.insert(request.id, request.number, image);
+
+						if request.action == b'T' {
+							self.place(&request, id).map(|_| Some(id))
+						}
+						else {
+							Ok(Some(id))
+						}
+					}
+
+					Err(err) =>
+						Err(err)
+				}
+			}
+
+			b'p' => {
+				let id = request.id.or_else(|| request.number.and_then(|n| self.images.find(n)));
+
+				if let Some(id) = id {
+					self.place(&request, id).map(|_| Some(id))
+				}
+				else {
+					Err(kitty::Error::Missing("image not found"))
+				}
+			}
+
+			// Deletions are never answered.
+			b'd' => {
+				self.unplace(&request);
+				return Ok(());
+			}
+
+			_ =>
+				Err(kitty::Error::Unsupported("unknown action"))
+		};
+
+		self.answer(&request, result, output)
+	}
+
+	/// Answer a kitty graphics protocol request.
+	#[cfg(feature = "images")]
+	fn answer<O: Write>(&self, request: &kitty::Request, result: Result<Option<u32>, kitty::Error>, mut output: O) -> io::Result<()> {
+		// Requests without identifiers are never answered.
+		if request.id.is_none() && request.number.is_none() {
+			return Ok(());
+		}
+
+		let message = match result {
+			Ok(_) if request.quiet == 0 => "OK".into(),
+			Err(err) if request.quiet < 2 => err.message(),
+			_ => return Ok(()),
+		};
+
+		let mut keys = Vec::new();
+
+		if let Some(id) = result.ok().and_then(|id| id).or(request.id) {
+			keys.push(format!("i={}", id));
+		}
+
+		if let Some(number) = request.number {
+			keys.push(format!("I={}", number));
+		}
+
+		if let Some(placement) = request.placement {
+			keys.push(format!("p={}", placement));
+		}
+
+		write!(output, "\x1B_G{};{}\x1B\\", keys.join(","), message)
+	}
+
+	/// Place an image at the cursor.
+	#[cfg(feature = "images")]
+	fn place(&mut self, request: &kitty::Request, id: u32) -> Result<(), kitty::Error> {
+		let (width, height) = {
+			let image = try!(self.images.get(id).ok_or(kitty::Error::Missing("image not found")));
+			(image.width(), image.height())
+		};
+
+		let (sx, sy)  = (cmp::min(request.source.0, width), cmp::min(request.source.1, height));
+		let (sw, sh)  = (
+			if request.source.2 == 0 { width - sx } else { cmp::min(request.source.2, width - sx) },
+			if request.source.3 == 0 { height - sy } else { cmp::min(request.source.3, height - sy) });
+
+		// The size is either given in cells, or the source size.
+		let size = (
+			if request.columns > 0 { request.columns as u64 * self.font.0 as u64 } else { sw as u64 },
+			if request.rows > 0 { request.rows as u64 * self.font.1 as u64 } else { sh as u64 });
+
+		let area = (request.offset.0 as u64 + size.0, request.offset.1 as u64 + size.1);
+		if area.0 > image::MAXIMUM as u64 || area.1 > image::MAXIMUM as u64 {
+			return Err(kitty::Error::Invalid("placement too big"));
+		}
+
+		let size    = (size.0 as u32, size.1 as u32);
+		let columns = cmp::max(1, (area.0 as u32 + self.font.0 - 1) / self.font.0);
+		let rows    = cmp::max(1, (area.1 as u32 + self.font.1 - 1) / self.font.1);
+
+		let (x, y)    = term!(self; cursor);
+		let placement = image::Placement {
+			image: id,
+			id:    request.placement.unwrap_or(0),
+
+			x:    x,
+			line: self.grid.scrolled() + y as i64,
+
+			columns: columns,
+			rows:    rows,
+
+			offset: request.offset,
+			source: (sx, sy, sw, sh),
+			size:   size,
+			z:      request.z,
+		};
+
+		if let Some(previous) = self.images.place(placement.clone()) {
+			self.touch(&previous);
+		}
+
+		self.touch(&placement);
+
+		// Move the cursor after the image, scrolling if needed.
+		if request.cursor {
+			for _ in 1 .. rows {
+				if term!(self; cursor Down(1)).is_some() {
+					term!(self; scroll! up 1);
+				}
+			}
+
+			term!(self; cursor Position(Some(x + columns), None));
+		}
+
+		Ok(())
+	}
+
+	/// Delete placements as specified by the request.
+	#[cfg(feature = "images")]
+	fn unplace(&mut self, request: &kitty::Request) {
+		let (x, y)   = term!(self; cursor);
+		let top      = self.grid.scrolled();
+		let bottom   = top + self.region.height as i64;
+		let free     = request.delete.is_ascii_uppercase();
+		let number   = request.number.and_then(|n| self.images.find(n));
+		let (px, py) = (request.source.0.saturating_sub(1), top + request.source.1.saturating_sub(1) as i64);
+
+		let removed = self.images.retain(|p| !match request.delete.to_ascii_lowercase() {
+			b'a' => p.line < bottom && p.line + p.rows as i64 > top,
+			b'i' => Some(p.image) == request.id && request.placement.map(|id| id == p.id).unwrap_or(true),
+			b'n' => Some(p.image) == number && request.placement.map(|id| id == p.id).unwrap_or(true),
+			b'c' => p.contains(x, top + y as i64),
+			b'p' => p.contains(px, py),
+			b'x' => p.contains(px, p.line),
+			b'y' => p.contains(p.x, py),
+			b'z' => p.z == request.z,
+			_    => false,
+		}, free);
+
+		for placement in &removed {
+			self.touch(placement);
+		}
+	}
+
+	/// Mark the visible cells covered by the placement as touched.
+	#[cfg(feature = "images")]
+	fn touch(&mut self, placement: &image::Placement) {
+		for line in placement.line .. placement.line + placement.rows as i64 {
+			let y = line - self.grid.scrolled();
+
+			if y < 0 || y >= self.region.height as i64 {
+				continue;
+			}
+
+			for x in placement.x .. cmp::min(placement.x + placement.columns, self.region.width) {
+				self.touched.mark(x, y as u32);
+			}
+		}
+	}
+
+	fn request<O: Write>(&mut self, request: &str, mut output: O) -> io::Result<()> {
+		debug!(target: "cancer::terminal::request", "request status: {:?}", request);
+
+		match request {
+			// Graphic rendition.
+			"m" => {
+				let     style  = self.cursor.style().clone();
+				let mut params = vec![String::from("0")];
+
+				for &(attr, value) in &[(style::BOLD, "1"), (style::FAINT, "2"), (style::ITALIC, "3"),
+				                        (style::UNDERLINE, "4"), (style::BLINK, "5"), (style::REVERSE, "7"),
+				                        (style::INVISIBLE, "8"), (style::STRUCK, "9")]
+				{
+					if style.attributes().contains(attr) {
+						params.push(value.into());
+					}
+				}
+
+				if let Some(color) = style.foreground() {
+					let (r, g, b, _): (u8, u8, u8, u8) = color.to_pixel();
+					params.push(format!("38;2;{};{};{}", r, g, b));
+				}
+
+				if let Some(color) = style.background() {
+					let (r, g, b, _): (u8, u8, u8, u8) = color.to_pixel();
+					params.push(format!("48;2;{};{};{}", r, g, b));
+				}
+
+				write!(output, "\x1BP1$r{}m\x1B\\", params.join(";"))
+			}
+
+			// Scrolling region.
+			"r" => {
+				write!(output, "\x1BP1$r{};{}r\x1B\\",
+					self.cursor.scroll.0 + 1, self.cursor.scroll.1 + 1)
+			}
+
+			// Cursor style.
+			" q" => {
+				let shape = match self.cursor.shape() {
+					Shape::Block => 1,
+					Shape::Line  => 3,
+					Shape::Beam  => 5,
+				};
+
+				write!(output, "\x1BP1$r{} q\x1B\\",
+					if self.cursor.blink() { shape } else { shape + 1 })
+			}
+
+			// Conformance level.
+			"\"p" => {
+				output.write_all(b"\x1BP1$r64;1\"p\x1B\\")
+			}
+
+			_ => {
+				output.write_all(b"\x1BP0$r\x1B\\")
+			}
+		}
+	}
+
 	fn command(&mut self, command: &str) -> Vec<Action> {
 		let mut actions = Vec::new();
 
@@ -1856,6 +3368,13 @@ impl Terminal {
 				self.touched.push(term!(self; cursor));
 			}
 
+			#[cfg(feature = "images")]
+			cmd if cmd.starts_with("1337;File=") => {
+				if let Some(file) = iterm::parse(&cmd[10..]) {
+					self.inline(file);
+				}
+			}
+
 			cmd if cmd.starts_with("clipboard:") => {
 				let mut parts = cmd.split(':').skip(1);
 
@@ -1881,6 +3400,12 @@ impl Index<(u32, u32)> for Terminal {
 	type Output = Cell;
 
 	fn index(&self, (x, y): (u32, u32)) -> &Self::Output {
+		if y >= self.region.height {
+			if let Some(status) = self.status.as_ref() {
+				return &status[x];
+			}
+		}
+
 		&self.grid[(x, y)]
 	}
 }
@@ -64,6 +64,22 @@ impl Touched {
 		self
 	}
 
+	/// Get the lines that have been touched in any way.
+	pub fn lines(&self, height: u32) -> Vec<u32> {
+		if self.all {
+			return (0 .. height).collect();
+		}
+
+		let mut lines = self.line.iter().cloned()
+			.chain(self.position.iter().map(|&(_, y)| y))
+			.filter(|&y| y < height)
+			.collect::<Vec<_>>();
+
+		lines.sort();
+		lines.dedup();
+		lines
+	}
+
 	/// Create an iterator out of the touched markers.
 	pub fn iter(&mut self, region: Region) -> Iter {
 		Iter::new(region,
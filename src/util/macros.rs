@@ -15,6 +15,7 @@
 // You should have received a copy of the GNU General Public License
 // along with cancer.  If not, see <http://www.gnu.org/licenses/>.
 
+#[macro_export]
 macro_rules! try {
 	(return option $body:expr) => (
 		if let Some(value) = $body {
@@ -84,6 +85,7 @@ macro_rules! try {
 	);
 }
 
+#[macro_export]
 macro_rules! vec_deque {
 	($value:expr; $size:expr) => ({
 		let mut value = VecDeque::new();
@@ -0,0 +1,118 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Compact storage of the scroll back.
+
+extern crate cancer;
+extern crate picto;
+
+use std::rc::Rc;
+
+use picto::color::Rgba;
+use cancer::{Grid, Style};
+use cancer::terminal::grid::History;
+
+fn scroll(grid: &mut Grid, lines: u32) {
+	for line in 0 .. lines {
+		let style = Rc::new(Style {
+			foreground: Some(Rgba::new_u8(line as u8, (line >> 8) as u8, 0, 255)),
+			.. Style::default()
+		});
+
+		grid[(0, 1)].make_occupied("x", style);
+		grid.up(1, None);
+		grid.clean_history();
+	}
+}
+
+#[test]
+fn styles() {
+	let mut grid = Grid::new(10, 2, History::Rows(4), None);
+
+	scroll(&mut grid, 16);
+	let size = grid.size();
+
+	// Styles only used by dropped rows are dropped too.
+	scroll(&mut grid, 1000);
+	assert_eq!(grid.size(), size);
+}
+
+#[test]
+fn bytes() {
+	let mut grid = Grid::new(10, 2, History::Bytes(4096), None);
+
+	scroll(&mut grid, 1000);
+	assert!(grid.size() <= 4096);
+}
+
+#[test]
+fn inflate() {
+	let mut grid = Grid::new(10, 2, History::Unlimited, None);
+
+	scroll(&mut grid, 8);
+	grid.inflate(-2, -1);
+
+	assert!(grid.back().get(5).is_none());
+	assert_eq!(grid.back().get(6).unwrap()[0].value(), "x");
+	assert_eq!(grid.back().get(7).unwrap()[0].value(), "x");
+	assert_eq!(grid.back().row(5).unwrap()[0].value(), "x");
+}
+
+#[test]
+fn reflow() {
+	let mut grid = Grid::new(10, 2, History::Unlimited, None);
+
+	for (y, text) in ["0123456789", "abcde", "short", ""].iter().enumerate() {
+		for (x, ch) in text.chars().enumerate() {
+			grid[(x as u32, 1)].make_occupied(ch.to_string(), Rc::new(Style::default()));
+		}
+
+		// The second row continues the first.
+		if y == 1 {
+			grid.wrapped(1, true);
+		}
+
+		grid.up(1, None);
+	}
+
+	grid.resize(20, 2);
+	let text = |row: &cancer::terminal::Row| row.iter().map(|c| c.value()).collect::<String>();
+
+	assert_eq!(grid.back().len(), 3);
+	assert_eq!(text(&grid.back().row(1).unwrap()).trim_right(), "0123456789abcde");
+	assert_eq!(text(&grid.back().row(2).unwrap()).trim_right(), "short");
+	assert_eq!(grid.back().row(2).unwrap().len(), 20);
+}
+
+#[test]
+fn spill() {
+	let mut grid = Grid::new(10, 2, History::Rows(4), Some(4096));
+
+	scroll(&mut grid, 1000);
+	let size = grid.size();
+
+	// Spilled rows keep their own styles, and the oldest ones are dropped.
+	scroll(&mut grid, 1000);
+	assert_eq!(grid.size(), size);
+	assert!(grid.back().spilled() > 0);
+	assert!(grid.back().spilled() < 1000);
+
+	let index = grid.back().spilled() - 1;
+	let row   = grid.back().row(index).unwrap();
+	assert_eq!(row[0].value(), "x");
+	assert_eq!(row[0].style().foreground, Some(Rgba::new_u8((994 % 256) as u8, (994 >> 8) as u8, 0, 255)));
+}
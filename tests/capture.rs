@@ -0,0 +1,89 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Serialization of the scroll back and view.
+
+extern crate cancer;
+
+use std::sync::Arc;
+
+use cancer::{Config, Terminal};
+use cancer::terminal::capture::{self, Format};
+
+fn terminal(columns: u32, rows: u32, input: &str) -> (Arc<Config>, Terminal) {
+	let config       = Arc::new(Config::default());
+	let mut terminal = Terminal::new(config.clone(), (8, 16), (columns, rows)).unwrap();
+	terminal.input(input, Vec::new()).unwrap();
+
+	(config, terminal)
+}
+
+#[test]
+fn wrapped() {
+	let (config, terminal) = terminal(10, 4, "first\r\n0123456789abc\r\nlast");
+
+	assert_eq!(capture::all(&config, terminal.grid(), Format::Text),
+		"first\n0123456789abc\nlast\n");
+}
+
+#[test]
+fn wrapped_into_history() {
+	let (config, terminal) = terminal(10, 2, "first\r\n0123456789abcdefghij0123\r\nlast");
+
+	assert_eq!(capture::all(&config, terminal.grid(), Format::Text),
+		"first\n0123456789abcdefghij0123\nlast\n");
+}
+
+#[test]
+fn range() {
+	let (config, terminal) = terminal(10, 4, "0123456789abc\r\nlast");
+
+	assert_eq!(capture::capture(&config, terminal.grid(), 0, 0, Format::Text),
+		"0123456789\n");
+}
+
+#[test]
+fn formats() {
+	let (config, terminal) = terminal(10, 2, "\x1B[1mfirst\x1B[0m\r\nsecond\r\nthird");
+
+	let html = capture::all(&config, terminal.grid(), Format::Html);
+	assert!(html.starts_with("<pre "));
+	assert!(html.ends_with(">first</span>\nsecond\nthird</pre>"));
+
+	let rtf = capture::all(&config, terminal.grid(), Format::Rtf);
+	assert!(rtf.starts_with("{\\rtf1"));
+	assert!(rtf.ends_with("third}\\line\n}"));
+	assert_eq!(rtf.matches("\\line").count(), 3);
+}
+
+#[test]
+fn save() {
+	use std::env;
+	use std::fs;
+	use std::os::unix::fs::PermissionsExt;
+
+	let (config, terminal) = terminal(10, 2, "secret");
+	let path = env::temp_dir().join(format!("cancer-capture-{}", std::process::id()));
+
+	capture::save(&config, terminal.grid(), path.to_str().unwrap(), Format::Text).unwrap();
+	let mode = fs::metadata(&path).unwrap().permissions().mode();
+	let text = fs::read_to_string(&path).unwrap();
+	fs::remove_file(&path).unwrap();
+
+	assert_eq!(mode & 0o777, 0o600);
+	assert_eq!(text, "secret\n\n");
+}
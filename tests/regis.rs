@@ -0,0 +1,68 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+//! ReGIS drawing on the offscreen canvas.
+
+#![cfg(feature = "images")]
+
+extern crate cancer;
+
+use cancer::terminal::Regis;
+
+#[test]
+fn dirty() {
+	let mut regis = Regis::new((80, 64));
+
+	regis.handle("P[0,0]V[100,0]");
+	let cells = regis.cells((8, 16));
+	assert!(!cells.is_empty());
+	assert!(cells.iter().all(|&((_, y), ref image)| y == 0 && image.is_some()));
+
+	// Nothing changed, nothing is returned.
+	assert!(regis.cells((8, 16)).is_empty());
+
+	// Only the cells of the new line are returned.
+	regis.handle("P[0,400]V[100,400]");
+	assert!(regis.cells((8, 16)).iter().all(|&((_, y), _)| y == 3));
+
+	// Erasing returns every cell that was drawn as empty.
+	regis.handle("S(E)");
+	let cells = regis.cells((8, 16));
+	assert!(!cells.is_empty());
+	assert!(cells.iter().all(|&(_, ref image)| image.is_none()));
+}
+
+#[test]
+fn resize() {
+	let mut regis = Regis::new((80, 64));
+
+	regis.handle("P[0,0]V[799,0]");
+	regis.cells((8, 16));
+
+	regis.resize((160, 128), (8, 16));
+	assert_eq!(regis.size(), (160, 128));
+	assert!(regis.image().as_ref().iter().any(|&b| b != 0));
+
+	// Drawing covers the new size.
+	regis.handle("P[0,479]V[799,479]");
+	assert!(regis.cells((8, 16)).iter().any(|&((_, y), _)| y == 7));
+
+	// Cells outside the smaller canvas aren't returned anymore.
+	regis.resize((40, 32), (8, 16));
+	regis.handle("S(E)");
+	assert!(regis.cells((8, 16)).iter().all(|&((x, y), _)| x < 5 && y < 2));
+}
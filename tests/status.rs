@@ -0,0 +1,37 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The status line below the grid.
+
+extern crate cancer;
+
+use std::sync::Arc;
+
+use cancer::{Config, Terminal};
+
+#[test]
+fn no_room() {
+	let mut terminal = Terminal::new(Arc::new(Config::default()), (8, 16), (10, 4)).unwrap();
+	terminal.input("\x1B[2$~", Vec::new()).unwrap();
+	assert!(terminal.status().is_some());
+	assert_eq!(terminal.region().height, 4);
+
+	// Without a row to spare the status line is dropped.
+	terminal.resize(10, 1);
+	assert!(terminal.status().is_none());
+	assert_eq!(terminal.region().height, 1);
+}
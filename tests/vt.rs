@@ -0,0 +1,273 @@
+// Copyleft (ↄ) meh. <meh@schizofreni.co> | http://meh.schizofreni.co
+//
+// This file is part of cancer.
+//
+// cancer is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// cancer is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with cancer.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Golden-file conformance tests.
+//!
+//! Every `tests/vt/*.in` case starts with a `COLUMNSxROWS` line, the rest is
+//! fed to a headless `Terminal` and the resulting state is compared with the
+//! dump in the matching `.out` file.
+//!
+//! In the input `\e`, `\r`, `\n`, `\t`, `\b`, `\\` and `\xHH` are escapes, actual
+//! newlines are ignored so long sequences can be split for readability, and
+//! `\|` splits the input in separate writes.
+//!
+//! A case without an expectation fails, set `CANCER_BLESS=1` to record new
+//! expectations and regenerate the ones that changed.
+
+extern crate cancer;
+
+use std::sync::Arc;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::fmt::Write as FmtWrite;
+use std::env;
+use std::mem;
+
+use cancer::{Config, Terminal, Style};
+
+/// Cases that depend on image support.
+const IMAGES: &'static [&'static str] = &["csi-xtsmgraphics", "osc-iterm-clamp"];
+
+#[test]
+fn vt() {
+	let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("vt");
+	let bless     = env::var("CANCER_BLESS").is_ok();
+
+	let mut cases = fs::read_dir(&directory).unwrap()
+		.map(|entry| entry.unwrap().path())
+		.filter(|path| path.extension().map(|e| e == "in").unwrap_or(false))
+		.collect::<Vec<PathBuf>>();
+	cases.sort();
+
+	let mut failed = Vec::new();
+
+	for case in &cases {
+		let name = case.file_stem().unwrap().to_string_lossy().into_owned();
+		if cfg!(not(feature = "images")) && IMAGES.contains(&&*name) {
+			continue;
+		}
+
+		let actual   = run(&read(case));
+		let expected = case.with_extension("out");
+
+		if bless {
+			File::create(&expected).unwrap().write_all(actual.as_bytes()).unwrap();
+			println!("recorded {}", name);
+			continue;
+		}
+
+		if fs::metadata(&expected).is_err() {
+			println!("--- {} (missing expectation)\n+++ {} (actual)\n{}", name, name, actual);
+			failed.push(name);
+			continue;
+		}
+
+		let expected = read(&expected);
+		if expected != actual {
+			println!("--- {} (expected)\n{}+++ {} (actual)\n{}", name, expected, name, actual);
+			failed.push(name);
+		}
+	}
+
+	assert!(failed.is_empty(), "failed cases: {}", failed.join(", "));
+}
+
+fn read<P: AsRef<Path>>(path: P) -> String {
+	let mut content = String::new();
+	File::open(path).unwrap().read_to_string(&mut content).unwrap();
+	content
+}
+
+/// Run a case and dump the terminal state.
+fn run(case: &str) -> String {
+	let (size, input) = case.split_at(case.find('\n').unwrap_or(case.len()));
+	let mut size      = size.trim().split('x').map(|v| v.parse::<u32>().unwrap());
+	let (columns, rows) = (size.next().unwrap(), size.next().unwrap());
+
+	let mut terminal = Terminal::new(Arc::new(Config::default()), (8, 16), (columns, rows)).unwrap();
+	let mut reply    = Vec::new();
+	let mut actions  = Vec::new();
+
+	for chunk in unescape(input) {
+		actions.extend(terminal.input(chunk, &mut reply).unwrap().0);
+	}
+
+	dump(&terminal, &reply, &actions)
+}
+
+/// Decode the escapes in the input, a `\|` splits it in separate writes.
+fn unescape(input: &str) -> Vec<Vec<u8>> {
+	let mut chunks = Vec::new();
+	let mut output = Vec::new();
+	let mut chars  = input.chars().filter(|&c| c != '\n');
+
+	while let Some(ch) = chars.next() {
+		if ch != '\\' {
+			let mut buffer = [0u8; 4];
+			output.extend_from_slice(ch.encode_utf8(&mut buffer).as_bytes());
+			continue;
+		}
+
+		match chars.next() {
+			Some('e')  => output.push(0x1B),
+			Some('r')  => output.push(b'\r'),
+			Some('n')  => output.push(b'\n'),
+			Some('t')  => output.push(b'\t'),
+			Some('b')  => output.push(0x08),
+			Some('\\') => output.push(b'\\'),
+
+			Some('x') => {
+				let hex = chars.by_ref().take(2).collect::<String>();
+				output.push(u8::from_str_radix(&hex, 16).unwrap());
+			}
+
+			Some('|') =>
+				chunks.push(mem::replace(&mut output, Vec::new())),
+
+			other =>
+				panic!("unknown escape: {:?}", other),
+		}
+	}
+
+	chunks.push(output);
+	chunks
+}
+
+/// Escape the control characters in a reply, 8-bit controls included.
+fn escape(bytes: &[u8]) -> String {
+	let mut output = String::new();
+
+	for &byte in bytes {
+		match byte {
+			0x1B                     => output.push_str("\\e"),
+			b'\r'                    => output.push_str("\\r"),
+			b'\n'                    => output.push_str("\\n"),
+			b'\\'                    => output.push_str("\\\\"),
+			b if b < b' ' || b > b'~' => write!(output, "\\x{:02X}", b).unwrap(),
+			b                        => output.push(b as char),
+		}
+	}
+
+	output
+}
+
+/// Describe a style, only what differs from the default is shown.
+fn style(style: &Style) -> String {
+	let mut parts = Vec::new();
+
+	if !style.attributes().is_empty() {
+		parts.push(format!("{:?}", style.attributes()).to_lowercase().replace(" | ", ","));
+	}
+
+	if let Some(color) = style.foreground() {
+		parts.push(format!("fg=#{:02x}{:02x}{:02x}",
+			(color.red * 255.0).round() as u8, (color.green * 255.0).round() as u8, (color.blue * 255.0).round() as u8));
+	}
+
+	if let Some(color) = style.background() {
+		parts.push(format!("bg=#{:02x}{:02x}{:02x}",
+			(color.red * 255.0).round() as u8, (color.green * 255.0).round() as u8, (color.blue * 255.0).round() as u8));
+	}
+
+	parts.join(" ")
+}
+
+/// Dump the grid text, wrapped flags, styles, cursor, modes and replies.
+fn dump<A: ::std::fmt::Debug>(terminal: &Terminal, reply: &[u8], actions: &[A]) -> String {
+	let mut output = String::new();
+	let     grid   = terminal.grid();
+	let     cursor = terminal.cursor();
+
+	writeln!(output, "size {}x{}", terminal.columns(), terminal.rows()).unwrap();
+	writeln!(output, "cursor {},{} wrap={} visible={} scroll={}-{}",
+		cursor.cell().x(), cursor.cell().y(), cursor.wrap(), cursor.is_visible(),
+		cursor.scroll().0, cursor.scroll().1).unwrap();
+	writeln!(output, "modes {:?}", terminal.mode()).unwrap();
+
+	if !reply.is_empty() {
+		writeln!(output, "reply {}", escape(reply)).unwrap();
+	}
+
+	for action in actions {
+		writeln!(output, "action {:?}", action).unwrap();
+	}
+
+	if !grid.back().is_empty() {
+		writeln!(output, "history {}", grid.back().len()).unwrap();
+	}
+
+	writeln!(output, "grid").unwrap();
+	for y in 0 .. terminal.rows() {
+		let mut line = String::new();
+
+		for x in 0 .. terminal.columns() {
+			let cell = &grid[(x, y)];
+
+			if cell.is_reference() {
+				continue;
+			}
+			else if cell.is_occupied() || cell.is_empty() {
+				line.push_str(cell.value());
+			}
+			else {
+				line.push('#');
+			}
+		}
+
+		writeln!(output, "{:>2}{}|{}|", y, if grid[y].is_wrapped() { "+" } else { " " }, line).unwrap();
+	}
+
+	if let Some(status) = terminal.status() {
+		let mut line = String::new();
+
+		for x in 0 .. status.columns() {
+			if !status[x].is_reference() {
+				line.push_str(status[x].value());
+			}
+		}
+
+		writeln!(output, "status |{}|", line).unwrap();
+	}
+
+	writeln!(output, "styles").unwrap();
+	for y in 0 .. terminal.rows() {
+		let mut x = 0;
+
+		while x < terminal.columns() {
+			if grid[(x, y)].is_reference() {
+				x += 1;
+				continue;
+			}
+
+			let current = style(grid[(x, y)].style());
+			let start   = x;
+
+			while x + 1 < terminal.columns() && (grid[(x + 1, y)].is_reference() || style(grid[(x + 1, y)].style()) == current) {
+				x += 1;
+			}
+
+			if !current.is_empty() {
+				writeln!(output, "{:>2} {}-{} {}", y, start, x, current).unwrap();
+			}
+
+			x += 1;
+		}
+	}
+
+	output
+}